@@ -5,6 +5,12 @@ pub struct Program {
     bin: Vec<u8>,
 }
 
+impl Default for Program {
+    fn default() -> Self {
+        Program::new()
+    }
+}
+
 impl Program {
     pub fn new() -> Program {
         Program { bin: Vec::new() }
@@ -0,0 +1,181 @@
+use std::f64::consts::PI;
+
+const PHASES: usize = 32;
+const WIDTH: usize = 16;
+const CUTOFF: f64 = 0.9;
+
+pub const DEFAULT_HIGH_PASS: f64 = 20.0;
+
+pub struct BlipBuffer {
+    clock_rate: u32,
+    sample_rate: u32,
+    origin: u64,
+    offset: u64,
+    end: u64,
+    end_cycle: u64,
+    buffer: Vec<f64>,
+    kernel: Vec<[f64; WIDTH]>,
+    integrator: f64,
+    low_pass: Option<f64>,
+    high_pass: Option<f64>,
+    low_pass_state: f64,
+    high_pass_input: f64,
+    high_pass_state: f64,
+}
+
+impl BlipBuffer {
+    pub fn new(clock_rate: u32, sample_rate: u32) -> BlipBuffer {
+        BlipBuffer {
+            clock_rate,
+            sample_rate,
+            origin: 0,
+            offset: 0,
+            end: 0,
+            end_cycle: 0,
+            buffer: Vec::new(),
+            kernel: BlipBuffer::step_kernel(),
+            integrator: 0.0,
+            low_pass: None,
+            high_pass: Some(DEFAULT_HIGH_PASS),
+            low_pass_state: 0.0,
+            high_pass_input: 0.0,
+            high_pass_state: 0.0,
+        }
+    }
+
+    pub fn clock_rate(&self) -> u32 {
+        self.clock_rate
+    }
+
+    pub fn set_clock_rate(&mut self, clock_rate: u32) {
+        self.clock_rate = clock_rate;
+        self.restart();
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.restart();
+    }
+
+    pub fn low_pass(&self) -> Option<f64> {
+        self.low_pass
+    }
+
+    pub fn set_low_pass(&mut self, cutoff: Option<f64>) {
+        self.low_pass = cutoff;
+    }
+
+    pub fn high_pass(&self) -> Option<f64> {
+        self.high_pass
+    }
+
+    pub fn set_high_pass(&mut self, cutoff: Option<f64>) {
+        self.high_pass = cutoff;
+    }
+
+    pub fn add_delta(&mut self, cycle: u64, delta: i32) {
+        if delta == 0 {
+            return;
+        }
+        let (index, phase) = self.sample_time(cycle);
+        let position = index.saturating_sub(self.offset) as usize;
+        if self.buffer.len() < position + WIDTH {
+            self.buffer.resize(position + WIDTH, 0.0);
+        }
+        for (tap, weight) in self.kernel[phase].iter().enumerate() {
+            self.buffer[position + tap] += weight * delta as f64;
+        }
+    }
+
+    pub fn end_frame(&mut self, cycle: u64) {
+        self.end_cycle = cycle;
+        self.end = self.sample_time(cycle).0.max(self.offset);
+    }
+
+    pub fn samples_available(&self) -> usize {
+        (self.end - self.offset) as usize
+    }
+
+    pub fn take_samples(&mut self) -> Vec<i16> {
+        let count = self.samples_available();
+        if self.buffer.len() < count {
+            self.buffer.resize(count, 0.0);
+        }
+        let mut samples = Vec::with_capacity(count);
+        for i in 0..count {
+            self.integrator += self.buffer[i];
+            let sample = self.filter(self.integrator);
+            samples.push(sample.round().clamp(-32768.0, 32767.0) as i16);
+        }
+        self.buffer.drain(0..count);
+        self.offset += count as u64;
+        samples
+    }
+
+    fn filter(&mut self, input: f64) -> f64 {
+        let mut value = input;
+        if let Some(cutoff) = self.high_pass {
+            let rc = 1.0 / (2.0 * PI * cutoff);
+            let alpha = rc / (rc + 1.0 / self.sample_rate as f64);
+            self.high_pass_state = alpha * (self.high_pass_state + value - self.high_pass_input);
+            self.high_pass_input = value;
+            value = self.high_pass_state;
+        }
+        if let Some(cutoff) = self.low_pass {
+            let rc = 1.0 / (2.0 * PI * cutoff);
+            let dt = 1.0 / self.sample_rate as f64;
+            self.low_pass_state += dt / (rc + dt) * (value - self.low_pass_state);
+            value = self.low_pass_state;
+        }
+        value
+    }
+
+    fn sample_time(&self, cycle: u64) -> (u64, usize) {
+        let elapsed = cycle.saturating_sub(self.origin) as u128;
+        let time = elapsed * self.sample_rate as u128 * PHASES as u128 / self.clock_rate as u128;
+        (
+            (time / PHASES as u128) as u64,
+            (time % PHASES as u128) as usize,
+        )
+    }
+
+    // Deltas still waiting in the buffer are summed into the integrator before they are
+    // dropped, so the output stays at the level the chips are at instead of keeping an offset.
+    fn restart(&mut self) {
+        self.integrator += self.buffer.iter().sum::<f64>();
+        self.origin = self.end_cycle;
+        self.offset = 0;
+        self.end = 0;
+        self.buffer.clear();
+    }
+
+    // Each phase holds a windowed sinc impulse, so that the running sum of the buffer yields
+    // band-limited steps instead of the hard edges that alias when point sampled.
+    fn step_kernel() -> Vec<[f64; WIDTH]> {
+        (0..PHASES)
+            .map(|phase| {
+                let mut taps = [0.0; WIDTH];
+                for (tap, weight) in taps.iter_mut().enumerate() {
+                    let x = tap as f64 - (WIDTH / 2) as f64 + 1.0 - phase as f64 / PHASES as f64;
+                    let sinc = if x == 0.0 {
+                        1.0
+                    } else {
+                        (PI * CUTOFF * x).sin() / (PI * CUTOFF * x)
+                    };
+                    let n = (tap as f64 + 1.0 - phase as f64 / PHASES as f64) / WIDTH as f64;
+                    let window = 0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos();
+                    *weight = sinc * window;
+                }
+                let sum: f64 = taps.iter().sum();
+                for weight in taps.iter_mut() {
+                    *weight /= sum;
+                }
+                taps
+            })
+            .collect()
+    }
+}
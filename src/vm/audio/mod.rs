@@ -0,0 +1,5 @@
+pub mod blip_buffer;
+pub mod pcm;
+pub mod sink;
+pub mod stereo_buffer;
+pub mod wav;
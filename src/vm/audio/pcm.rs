@@ -0,0 +1,58 @@
+use std::io::{self, Write};
+
+use crate::vm::audio::sink::{downmix, AudioSink};
+
+pub struct PcmWriter<W: Write> {
+    writer: W,
+    channels: u16,
+    sample_rate: u32,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> PcmWriter<W> {
+    pub fn new(writer: W, channels: u16, sample_rate: u32) -> PcmWriter<W> {
+        PcmWriter {
+            writer,
+            channels,
+            sample_rate,
+            error: None,
+        }
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn into_inner(self) -> io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.writer),
+        }
+    }
+}
+
+impl<W: Write> AudioSink for PcmWriter<W> {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn write_samples(&mut self, samples: &[i16]) {
+        if self.error.is_some() {
+            return;
+        }
+        let bytes: Vec<u8> = downmix(samples, self.channels)
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes().to_vec())
+            .collect();
+        if let Err(error) = self.writer.write_all(&bytes) {
+            self.error = Some(error);
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => self.writer.flush(),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use std::io;
+
+pub trait AudioSink {
+    fn sample_rate(&self) -> u32;
+    fn write_samples(&mut self, samples: &[i16]);
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+// Machine audio is interleaved stereo; mono sinks average each pair of samples.
+pub(crate) fn downmix(samples: &[i16], channels: u16) -> Vec<i16> {
+    if channels == 1 {
+        samples
+            .chunks(2)
+            .map(|pair| ((pair[0] as i32 + *pair.get(1).unwrap_or(&pair[0]) as i32) / 2) as i16)
+            .collect()
+    } else {
+        samples.to_vec()
+    }
+}
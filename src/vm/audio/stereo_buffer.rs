@@ -0,0 +1,82 @@
+use crate::vm::audio::blip_buffer::BlipBuffer;
+
+pub struct StereoBuffer {
+    left: BlipBuffer,
+    right: BlipBuffer,
+}
+
+impl StereoBuffer {
+    pub fn new(clock_rate: u32, sample_rate: u32) -> StereoBuffer {
+        StereoBuffer {
+            left: BlipBuffer::new(clock_rate, sample_rate),
+            right: BlipBuffer::new(clock_rate, sample_rate),
+        }
+    }
+
+    pub fn left(&self) -> &BlipBuffer {
+        &self.left
+    }
+
+    pub fn right(&self) -> &BlipBuffer {
+        &self.right
+    }
+
+    pub fn clock_rate(&self) -> u32 {
+        self.left.clock_rate()
+    }
+
+    pub fn set_clock_rate(&mut self, clock_rate: u32) {
+        self.left.set_clock_rate(clock_rate);
+        self.right.set_clock_rate(clock_rate);
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.left.sample_rate()
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.left.set_sample_rate(sample_rate);
+        self.right.set_sample_rate(sample_rate);
+    }
+
+    pub fn low_pass(&self) -> Option<f64> {
+        self.left.low_pass()
+    }
+
+    pub fn set_low_pass(&mut self, cutoff: Option<f64>) {
+        self.left.set_low_pass(cutoff);
+        self.right.set_low_pass(cutoff);
+    }
+
+    pub fn high_pass(&self) -> Option<f64> {
+        self.left.high_pass()
+    }
+
+    pub fn set_high_pass(&mut self, cutoff: Option<f64>) {
+        self.left.set_high_pass(cutoff);
+        self.right.set_high_pass(cutoff);
+    }
+
+    pub fn add_delta(&mut self, cycle: u64, left: i32, right: i32) {
+        self.left.add_delta(cycle, left);
+        self.right.add_delta(cycle, right);
+    }
+
+    pub fn end_frame(&mut self, cycle: u64) {
+        self.left.end_frame(cycle);
+        self.right.end_frame(cycle);
+    }
+
+    pub fn frames_available(&self) -> usize {
+        self.left.samples_available()
+    }
+
+    pub fn take_samples(&mut self) -> Vec<i16> {
+        let left = self.left.take_samples();
+        let right = self.right.take_samples();
+        left.iter()
+            .zip(right.iter())
+            .flat_map(|(l, r)| vec![*l, *r])
+            .collect()
+    }
+}
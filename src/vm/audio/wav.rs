@@ -0,0 +1,90 @@
+use std::io::{self, Seek, SeekFrom, Write};
+
+use crate::vm::audio::sink::{downmix, AudioSink};
+
+const HEADER_SIZE: u32 = 44;
+const BITS_PER_SAMPLE: u16 = 16;
+
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    channels: u16,
+    sample_rate: u32,
+    data_size: u32,
+    error: Option<io::Error>,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(mut writer: W, channels: u16, sample_rate: u32) -> io::Result<WavWriter<W>> {
+        writer.write_all(&WavWriter::<W>::header(channels, sample_rate, 0))?;
+        Ok(WavWriter {
+            writer,
+            channels,
+            sample_rate,
+            data_size: 0,
+            error: None,
+        })
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn frames_written(&self) -> u32 {
+        self.data_size / (self.channels as u32 * 2)
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.finish()?;
+        Ok(self.writer)
+    }
+
+    fn header(channels: u16, sample_rate: u32, data_size: u32) -> Vec<u8> {
+        let block_align = channels * BITS_PER_SAMPLE / 8;
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&(HEADER_SIZE - 8 + data_size).to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&channels.to_le_bytes());
+        header.extend_from_slice(&sample_rate.to_le_bytes());
+        header.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        header.extend_from_slice(&block_align.to_le_bytes());
+        header.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&data_size.to_le_bytes());
+        header
+    }
+}
+
+impl<W: Write + Seek> AudioSink for WavWriter<W> {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn write_samples(&mut self, samples: &[i16]) {
+        if self.error.is_some() {
+            return;
+        }
+        let bytes: Vec<u8> = downmix(samples, self.channels)
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes().to_vec())
+            .collect();
+        match self.writer.write_all(&bytes) {
+            Ok(()) => self.data_size += bytes.len() as u32,
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    // Rewrites the header with the final sizes, leaving the writer positioned at the end.
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        let header = WavWriter::<W>::header(self.channels, self.sample_rate, self.data_size);
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(&header)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,169 @@
+use crate::vm::io::peripheral::{Peripheral, PORT_COUNT};
+use crate::vm::io::Region;
+use crate::vm::machine::{Machine, DEFAULT_SAMPLE_RATE};
+use crate::vm::power::PowerOnPattern;
+use crate::vm::ram::MemoryMap;
+use crate::vm::system::System;
+use crate::vm::vdp::{Vdp, VdpModel};
+
+const CONSOLE_RAM_SIZE: usize = 0x2000;
+
+// Presets choose each console's chips, region and RAM. BIOS images are not bundled, so they
+// are passed to `bios`, and every preset starts with joypads in both ports.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Preset {
+    MarkIII,
+    Sms1,
+    // The Japanese Master System has the FM sound unit built in.
+    SmsJapan,
+    Sms2,
+    GameGear,
+    Sg1000,
+    GenericZ80,
+}
+
+pub struct MachineBuilder {
+    system: System,
+    vdp_model: Option<VdpModel>,
+    region: Region,
+    fm_unit: bool,
+    memory_map: Option<MemoryMap>,
+    bios: Option<Vec<u8>>,
+    cartridge: Option<Vec<u8>>,
+    peripherals: [Peripheral; PORT_COUNT],
+    sample_rate: u32,
+    power_on_pattern: PowerOnPattern,
+}
+
+impl Default for MachineBuilder {
+    fn default() -> Self {
+        MachineBuilder::new()
+    }
+}
+
+impl MachineBuilder {
+    pub fn new() -> MachineBuilder {
+        MachineBuilder::preset(Preset::Sms2)
+    }
+
+    pub fn preset(preset: Preset) -> MachineBuilder {
+        let system = match preset {
+            Preset::Sg1000 => System::Sg1000,
+            Preset::GenericZ80 => System::Generic,
+            _ => System::MasterSystem,
+        };
+        let vdp_model = match preset {
+            Preset::MarkIII | Preset::Sms1 | Preset::SmsJapan => Some(VdpModel::Sms1),
+            Preset::GameGear => Some(VdpModel::GameGear),
+            _ => None,
+        };
+        let region = match preset {
+            Preset::MarkIII | Preset::SmsJapan | Preset::Sg1000 => Region::Japan,
+            _ => Region::ExportNtsc,
+        };
+        let memory_map = match preset {
+            Preset::Sg1000 | Preset::GenericZ80 => None,
+            _ => Some(MemoryMap::with_ram_size(CONSOLE_RAM_SIZE)),
+        };
+        MachineBuilder {
+            system,
+            vdp_model,
+            region,
+            fm_unit: preset == Preset::SmsJapan,
+            memory_map,
+            bios: None,
+            cartridge: None,
+            peripherals: [Peripheral::Joypad; PORT_COUNT],
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            power_on_pattern: PowerOnPattern::Zero,
+        }
+    }
+
+    // Switching the system also goes back to its own VDP and memory map, so call `vdp_model`
+    // and `memory_map` afterwards to override them.
+    pub fn system(mut self, system: System) -> MachineBuilder {
+        self.system = system;
+        self.vdp_model = None;
+        self.memory_map = None;
+        self
+    }
+
+    pub fn vdp_model(mut self, model: VdpModel) -> MachineBuilder {
+        self.vdp_model = Some(model);
+        self
+    }
+
+    pub fn region(mut self, region: Region) -> MachineBuilder {
+        self.region = region;
+        self
+    }
+
+    pub fn fm_unit(mut self, enabled: bool) -> MachineBuilder {
+        self.fm_unit = enabled;
+        self
+    }
+
+    pub fn memory_map(mut self, map: MemoryMap) -> MachineBuilder {
+        self.memory_map = Some(map);
+        self
+    }
+
+    pub fn ram_size(self, size: usize) -> MachineBuilder {
+        self.memory_map(MemoryMap::with_ram_size(size))
+    }
+
+    pub fn bios(mut self, bios: &[u8]) -> MachineBuilder {
+        self.bios = Some(bios.to_vec());
+        self
+    }
+
+    pub fn cartridge(mut self, rom: &[u8]) -> MachineBuilder {
+        self.cartridge = Some(rom.to_vec());
+        self
+    }
+
+    pub fn peripheral(mut self, port: usize, peripheral: Peripheral) -> MachineBuilder {
+        self.peripherals[port] = peripheral;
+        self
+    }
+
+    pub fn sample_rate(mut self, rate: u32) -> MachineBuilder {
+        self.sample_rate = rate;
+        self
+    }
+
+    pub fn power_on_pattern(mut self, pattern: PowerOnPattern) -> MachineBuilder {
+        self.power_on_pattern = pattern;
+        self
+    }
+
+    // Images that neither fit below the RAM nor go through the mapper are dropped, as with
+    // `load_cartridge`.
+    pub fn build(self) -> Machine {
+        let mut machine = Machine::with_system(self.system);
+        if let Some(model) = self.vdp_model {
+            if machine.vdp.model() != model {
+                machine.vdp = Vdp::with_model(model);
+                machine.vdp.set_sms_mode(self.system == System::MasterGear);
+            }
+        }
+        if let Some(map) = self.memory_map {
+            machine.ram.set_map(map);
+        }
+        machine.set_region(self.region);
+        machine.set_fm_unit(self.fm_unit);
+        machine.set_sample_rate(self.sample_rate);
+        machine.set_power_on_pattern(self.power_on_pattern);
+        machine.power_cycle();
+        for (port, peripheral) in self.peripherals.iter().enumerate() {
+            machine.set_peripheral(port, *peripheral);
+        }
+        if let Some(rom) = self.cartridge.as_ref() {
+            machine.load_cartridge(rom);
+        }
+        if let Some(bios) = self.bios.as_ref() {
+            machine.load_bios(bios);
+        }
+        machine
+    }
+}
@@ -0,0 +1,37 @@
+use crate::vm::cpu::alu;
+use crate::vm::machine::Machine;
+
+impl Machine {
+    // Memory reads from the CPU. Addresses with nothing mapped read back the floating data bus,
+    // and every transfer leaves its byte on the bus.
+    pub fn read_memory(&mut self, address: u16) -> u8 {
+        let value = if self.ram.is_mapped(address) {
+            self.ram.read_u8(address)
+        } else {
+            self.open_bus()
+        };
+        self.data_bus = value;
+        value
+    }
+
+    pub fn read_memory_u16(&mut self, address: u16) -> u16 {
+        let low = self.read_memory(address) as u16;
+        let high = self.read_memory(address.wrapping_add(1)) as u16;
+        (high << 8) | low
+    }
+
+    // Memory writes from the CPU go through here so devices listening on the card slot see
+    // them as well as RAM.
+    pub fn write_memory(&mut self, address: u16, value: u8) {
+        self.data_bus = value;
+        self.observe_glasses_write(address, value);
+        self.ram.write_u8(address, value);
+        self.observe_paging_write(address, value);
+    }
+
+    pub fn write_memory_u16(&mut self, address: u16, value: u16) {
+        let (high, low) = alu::get_octets(value);
+        self.write_memory(address, low);
+        self.write_memory(address.wrapping_add(1), high);
+    }
+}
@@ -2,13 +2,22 @@ use crate::vm::instructions::mnemonics::Mnemonic;
 use crate::vm::machine::Machine;
 use std::collections::HashMap;
 
+pub type MachineCallback = Box<dyn FnMut(&mut Machine)>;
+pub type InstructionCallback = Box<dyn FnMut(&mut Machine, Mnemonic)>;
+
 pub struct Callbacks {
-    before_instruction_fetch: Vec<Box<FnMut(&mut Machine)>>,
-    before_instruction_exec: Vec<Box<FnMut(&mut Machine, Mnemonic)>>,
-    after_instruction_exec: Vec<Box<FnMut(&mut Machine, Mnemonic)>>,
+    before_instruction_fetch: Vec<MachineCallback>,
+    before_instruction_exec: Vec<InstructionCallback>,
+    after_instruction_exec: Vec<InstructionCallback>,
 
-    before_instruction_exec_match: HashMap<Mnemonic, Vec<Box<FnMut(&mut Machine)>>>,
-    after_instruction_exec_match: HashMap<Mnemonic, Vec<Box<FnMut(&mut Machine)>>>,
+    before_instruction_exec_match: HashMap<Mnemonic, Vec<MachineCallback>>,
+    after_instruction_exec_match: HashMap<Mnemonic, Vec<MachineCallback>>,
+}
+
+impl Default for Callbacks {
+    fn default() -> Self {
+        Callbacks::new()
+    }
 }
 
 impl Callbacks {
@@ -23,7 +32,7 @@ impl Callbacks {
         }
     }
 
-    pub fn on_before_instruction_fetch(&mut self, callback: Box<FnMut(&mut Machine)>) {
+    pub fn on_before_instruction_fetch(&mut self, callback: MachineCallback) {
         self.before_instruction_fetch.push(callback);
     }
 
@@ -33,7 +42,7 @@ impl Callbacks {
         }
     }
 
-    pub fn on_before_instruction_exec(&mut self, callback: Box<FnMut(&mut Machine, Mnemonic)>) {
+    pub fn on_before_instruction_exec(&mut self, callback: InstructionCallback) {
         self.before_instruction_exec.push(callback);
     }
 
@@ -47,7 +56,7 @@ impl Callbacks {
         }
     }
 
-    pub fn on_after_instruction_exec(&mut self, callback: Box<FnMut(&mut Machine, Mnemonic)>) {
+    pub fn on_after_instruction_exec(&mut self, callback: InstructionCallback) {
         self.after_instruction_exec.push(callback);
     }
 
@@ -64,17 +73,12 @@ impl Callbacks {
     pub fn on_before_instruction_exec_match(
         &mut self,
         instruction: Mnemonic,
-        callback: Box<FnMut(&mut Machine)>,
+        callback: MachineCallback,
     ) {
-        if !self
-            .before_instruction_exec_match
-            .contains_key(&instruction)
-        {
-            self.before_instruction_exec_match
-                .insert(instruction, Vec::new());
-        }
-        let list = self.before_instruction_exec_match.get_mut(&instruction);
-        list.unwrap().push(callback);
+        self.before_instruction_exec_match
+            .entry(instruction)
+            .or_default()
+            .push(callback);
     }
 
     pub(crate) fn do_before_instruction_exec_match(
@@ -99,14 +103,12 @@ impl Callbacks {
     pub fn on_after_instruction_exec_match(
         &mut self,
         instruction: Mnemonic,
-        callback: Box<FnMut(&mut Machine)>,
+        callback: MachineCallback,
     ) {
-        if !self.after_instruction_exec_match.contains_key(&instruction) {
-            self.after_instruction_exec_match
-                .insert(instruction, Vec::new());
-        }
-        let list = self.after_instruction_exec_match.get_mut(&instruction);
-        list.unwrap().push(callback);
+        self.after_instruction_exec_match
+            .entry(instruction)
+            .or_default()
+            .push(callback);
     }
 
     pub(crate) fn do_after_instruction_exec_match(
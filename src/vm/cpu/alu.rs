@@ -59,9 +59,9 @@ pub(crate) fn add_octets(a: u8, b: u8) -> AdderResult<u8> {
     };
     AdderResult {
         value: result,
-        half_carry: half_carry,
-        carry: carry,
-        overflow: overflow,
+        half_carry,
+        carry,
+        overflow,
     }
 }
 
@@ -92,7 +92,7 @@ pub(crate) fn add_words(a: u16, b: u16) -> AdderResult<u16> {
         value: result,
         half_carry: high.half_carry,
         carry: high.carry,
-        overflow: overflow,
+        overflow,
     }
 }
 
@@ -38,9 +38,8 @@ impl Flag {
     pub(crate) fn set_values(state: &mut State, affected: &[Flag], values: &[(Flag, bool)]) {
         let map: HashMap<Flag, bool> = values.iter().cloned().collect();
         for flag in affected {
-            match map.get(&flag) {
-                Some(value) => flag.set(state, *value),
-                None => {}
+            if let Some(value) = map.get(flag) {
+                flag.set(state, *value);
             }
         }
     }
@@ -4,6 +4,15 @@ use crate::vm::cpu::state::State;
 pub struct Processor {
     pub state: State,
     halted: bool,
+    iff1: bool,
+    iff2: bool,
+    interrupt_delay: bool,
+}
+
+impl Default for Processor {
+    fn default() -> Self {
+        Processor::new()
+    }
 }
 
 impl Processor {
@@ -11,6 +20,9 @@ impl Processor {
         Processor {
             state: State::new(),
             halted: false,
+            iff1: false,
+            iff2: false,
+            interrupt_delay: false,
         }
     }
 
@@ -24,6 +36,62 @@ impl Processor {
         self.halted = false;
     }
 
+    pub fn interrupts_enabled(&self) -> bool {
+        self.iff1
+    }
+    pub fn enable_interrupts(&mut self) {
+        self.iff1 = true;
+        self.iff2 = true;
+        self.interrupt_delay = true;
+    }
+    pub fn disable_interrupts(&mut self) {
+        self.iff1 = false;
+        self.iff2 = false;
+    }
+
+    pub(crate) fn accept_nmi(&mut self) {
+        self.iff2 = self.iff1;
+        self.iff1 = false;
+        self.interrupt_delay = false;
+    }
+
+    // An `ei` only takes effect after the instruction that follows it.
+    pub(crate) fn accepts_interrupt(&mut self) -> bool {
+        if self.interrupt_delay {
+            self.interrupt_delay = false;
+            false
+        } else {
+            self.iff1
+        }
+    }
+
+    // The reset line only clears the program counter and interrupt state.
+    pub fn reset(&mut self) {
+        self.halted = false;
+        self.disable_interrupts();
+        self.interrupt_delay = false;
+        self.goto(0x0000);
+    }
+
+    // Loads the main, alternate and stack pointer registers from `values`, two bytes each.
+    pub(crate) fn power_on(&mut self, values: &[u8]) {
+        let mut pairs = [
+            &mut self.state.registers.af,
+            &mut self.state.registers.bc,
+            &mut self.state.registers.de,
+            &mut self.state.registers.hl,
+            &mut self.state.alt_registers.af,
+            &mut self.state.alt_registers.bc,
+            &mut self.state.alt_registers.de,
+            &mut self.state.alt_registers.hl,
+            &mut self.state.sp,
+        ];
+        for (pair, bytes) in pairs.iter_mut().zip(values.chunks(2)) {
+            **pair = (bytes[0], bytes[1]);
+        }
+        self.reset();
+    }
+
     pub fn goto(&mut self, address: u16) {
         self.state.pc = alu::get_octets(address);
     }
@@ -11,6 +11,12 @@ pub struct Registers {
     pub hl: (Register, Register),
 }
 
+impl Default for Registers {
+    fn default() -> Self {
+        Registers::new()
+    }
+}
+
 impl Registers {
     pub fn new() -> Registers {
         Registers {
@@ -8,6 +8,12 @@ pub struct State {
     pub sp: (u8, u8),
 }
 
+impl Default for State {
+    fn default() -> Self {
+        State::new()
+    }
+}
+
 impl State {
     pub fn new() -> State {
         State {
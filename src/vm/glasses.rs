@@ -0,0 +1,99 @@
+use crate::vm::machine::Machine;
+use crate::vm::vdp::framebuffer::Framebuffer;
+use crate::vm::vdp::Viewport;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GlassesMode {
+    LeftOnly,
+    RightOnly,
+    Anaglyph,
+    SideBySide,
+}
+
+impl Machine {
+    pub fn glasses_shutter(&self) -> Option<u8> {
+        self.glasses_shutter
+    }
+
+    // The eye the frame being drawn is shown to, latched from the shutter as the frame begins.
+    // Games switch the shutter during the vertical blank for the frame that follows it.
+    pub fn glasses_eye(&self) -> Option<Eye> {
+        self.frame_eye
+    }
+
+    // The SegaScope 3-D glasses listen for writes to 0xFFF8-0xFFFB, which still reach RAM.
+    pub(crate) fn observe_glasses_write(&mut self, address: u16, value: u8) {
+        if (0xFFF8..=0xFFFB).contains(&address) {
+            self.glasses_shutter = Some(value);
+        }
+    }
+
+    // Bit 0 of the shutter value opens the left lens; the other lens is closed.
+    pub(crate) fn latch_glasses_eye(&mut self) {
+        self.frame_eye = self.glasses_shutter.map(|value| {
+            if value & 0x01 != 0 {
+                Eye::Left
+            } else {
+                Eye::Right
+            }
+        });
+    }
+
+    // Combines the latest frame shown to each eye; without 3-D output both eyes see the same
+    // frame.
+    pub fn glasses_framebuffer(&self, mode: GlassesMode) -> Framebuffer {
+        let current = self.vdp.screen(Viewport::Visible);
+        let left = self.eye_frames[0].as_ref().unwrap_or(&current);
+        let right = self.eye_frames[1].as_ref().unwrap_or(&current);
+        match mode {
+            GlassesMode::LeftOnly => left.clone(),
+            GlassesMode::RightOnly => right.clone(),
+            GlassesMode::Anaglyph => {
+                let (width, height) = common_size(left, right);
+                let mut output = Framebuffer::new(width, height);
+                for y in 0..height {
+                    let line = output.line_mut(y);
+                    for (x, pixel) in line.iter_mut().enumerate() {
+                        *pixel = (left.pixel(x, y) & 0xFF0000) | (right.pixel(x, y) & 0x00FFFF);
+                    }
+                }
+                output
+            }
+            GlassesMode::SideBySide => {
+                let (width, height) = common_size(left, right);
+                let mut output = Framebuffer::new(width * 2, height);
+                for y in 0..height {
+                    let line = output.line_mut(y);
+                    for x in 0..width {
+                        line[x] = left.pixel(x, y);
+                        line[width + x] = right.pixel(x, y);
+                    }
+                }
+                output
+            }
+        }
+    }
+
+    pub(crate) fn capture_eye_frame(&mut self, eye: Option<Eye>, framebuffer: &Framebuffer) {
+        match eye {
+            Some(Eye::Left) => self.eye_frames[0] = Some(framebuffer.clone()),
+            Some(Eye::Right) => self.eye_frames[1] = Some(framebuffer.clone()),
+            None => {}
+        }
+    }
+}
+
+// The eyes' frames differ in size when the game changes the screen height between them; only
+// the area both cover is combined.
+fn common_size(left: &Framebuffer, right: &Framebuffer) -> (usize, usize) {
+    (
+        left.width().min(right.width()),
+        left.height().min(right.height()),
+    )
+}
@@ -16,7 +16,7 @@ impl Machine {
 
     pub(crate) fn add_carry_memory(&mut self) {
         let address = self.get_register_pair(Registers::hl());
-        let operand = self.ram.read_u8(address);
+        let operand = self.read_memory(address);
         let carry = Flag::Carry.get_bit(&self.cpu.state);
         self.operate_on_register(
             Operation::Add,
@@ -135,7 +135,7 @@ impl Machine {
 
     fn op_accumulator_memory(&mut self, operation: Operation) {
         let address = self.get_register_pair(|state| state.registers.hl);
-        let operand = self.ram.read_u8(address);
+        let operand = self.read_memory(address);
         self.operate_on_register(
             operation,
             |state| &mut state.registers.af.0,
@@ -179,10 +179,10 @@ impl Machine {
 
     fn operate_on_memory(&mut self, operation: Operation, operand: u8, affected_flags: &[Flag]) {
         let address = self.get_register_pair(|state| state.registers.hl);
-        let op1 = self.ram.read_u8(address);
+        let op1 = self.read_memory(address);
         let op2 = operation.maybe_negate(operand);
         let result = alu::add_octets(op1, op2);
-        self.ram.write_u8(address, result.value);
+        self.write_memory(address, result.value);
         Flag::set_values(
             &mut self.cpu.state,
             affected_flags,
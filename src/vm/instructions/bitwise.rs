@@ -53,7 +53,7 @@ impl Machine {
         let op1 = self.cpu.state.registers.af.0;
         let op2 = operand;
         let result = operation(op1, op2);
-        let parity = (0..8).fold(0, |acc, b| acc + (result >> b) & 1) % 2 == 0;
+        let parity = (0..8).fold(0, |acc, b| acc + ((result >> b) & 1)) % 2 == 0;
 
         let state = &mut self.cpu.state;
         Flag::ParityOverflow.set(state, parity);
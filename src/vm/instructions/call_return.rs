@@ -14,6 +14,12 @@ impl Machine {
         }
     }
 
+    pub(crate) fn restart(&mut self, address: u16) {
+        self.push_program_counter_to_stack();
+        self.cpu.goto(address);
+        self.clock(11);
+    }
+
     pub(crate) fn ret(&mut self) {
         self.pop_stack_to_program_counter();
         self.clock(10);
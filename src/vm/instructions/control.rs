@@ -12,6 +12,16 @@ impl Machine {
         self.clock(4);
     }
 
+    pub(crate) fn disable_interrupts(&mut self) {
+        self.cpu.disable_interrupts();
+        self.clock(4);
+    }
+
+    pub(crate) fn enable_interrupts(&mut self) {
+        self.cpu.enable_interrupts();
+        self.clock(4);
+    }
+
     pub(crate) fn set_carry_flag(&mut self) {
         {
             let state = &mut self.cpu.state;
@@ -4,6 +4,9 @@ use crate::vm::cpu::state::State;
 use crate::vm::machine::Machine;
 use std::mem;
 
+type PairSelector = fn(&mut State) -> (&mut (u8, u8), &mut (u8, u8));
+type ShadowSelector = fn(&mut Registers) -> &mut (u8, u8);
+
 impl Machine {
     pub(crate) fn shadow_exchange_af(&mut self) {
         self.exchange_with_shadow(&[|regs| &mut regs.af]);
@@ -28,21 +31,21 @@ impl Machine {
         {
             let sp = alu::get_word(self.cpu.state.sp);
             let reg_value = alu::get_word(self.cpu.state.registers.hl);
-            let mem_value = self.ram.read_u16(sp);
+            let mem_value = self.read_memory_u16(sp);
             self.cpu.state.registers.hl = alu::get_octets(mem_value);
-            self.ram.write_u16(sp, reg_value);
+            self.write_memory_u16(sp, reg_value);
         }
         self.clock(19);
     }
 
-    fn exchange(&mut self, selectors: &[fn(&mut State) -> (&mut (u8, u8), &mut (u8, u8))]) {
+    fn exchange(&mut self, selectors: &[PairSelector]) {
         for s in selectors {
             let (r1, r2) = s(&mut self.cpu.state);
             mem::swap(r1, r2);
         }
     }
 
-    fn exchange_with_shadow(&mut self, selectors: &[fn(&mut Registers) -> &mut (u8, u8)]) {
+    fn exchange_with_shadow(&mut self, selectors: &[ShadowSelector]) {
         let reg = &mut self.cpu.state.registers;
         let alt = &mut self.cpu.state.alt_registers;
         for s in selectors {
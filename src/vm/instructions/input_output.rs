@@ -0,0 +1,19 @@
+use crate::vm::machine::Machine;
+
+impl Machine {
+    pub(crate) fn input_from_port_into_accumulator(&mut self) {
+        let port = self.next_byte();
+        let value = self.read_port(port);
+        self.data_bus = value;
+        self.cpu.state.registers.af.0 = value;
+        self.clock(11);
+    }
+
+    pub(crate) fn output_accumulator_to_port(&mut self) {
+        let port = self.next_byte();
+        let value = self.cpu.state.registers.af.0;
+        self.clock(11);
+        self.data_bus = value;
+        self.write_port(port, value);
+    }
+}
@@ -9,7 +9,7 @@ impl Machine {
     ) {
         let address = self.next_word();
         let value = alu::get_word(selector(&self.cpu.state));
-        self.ram.write_u16(address, value);
+        self.write_memory_u16(address, value);
         self.clock(16);
     }
 
@@ -19,8 +19,8 @@ impl Machine {
     ) {
         {
             let address = self.next_word();
+            let value = self.read_memory_u16(address);
             let (high_addr, low_addr) = selector(&mut self.cpu.state);
-            let value = self.ram.read_u16(address);
             let (high_val, low_val) = alu::get_octets(value);
             *high_addr = high_val;
             *low_addr = low_val;
@@ -31,7 +31,7 @@ impl Machine {
     pub(crate) fn load_param_into_memory(&mut self, selector: fn(&State) -> (u8, u8)) {
         let address = alu::get_word(selector(&self.cpu.state));
         let value = self.next_byte();
-        self.ram.write_u8(address, value);
+        self.write_memory(address, value);
         self.clock(10);
     }
 
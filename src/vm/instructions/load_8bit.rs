@@ -24,7 +24,7 @@ impl Machine {
     ) {
         {
             let address = pointer(&self.cpu.state);
-            let value = self.ram.read_u8(address);
+            let value = self.read_memory(address);
             let dest = selector(&mut self.cpu.state);
             *dest = value;
         }
@@ -39,7 +39,7 @@ impl Machine {
         {
             let address = alu::get_word(pointer(&self.cpu.state));
             let value = selector(&self.cpu.state);
-            self.ram.write_u8(address, value);
+            self.write_memory(address, value);
         }
         self.clock(7);
     }
@@ -47,14 +47,14 @@ impl Machine {
     pub(crate) fn load_register_into_param_memory(&mut self, selector: fn(&State) -> u8) {
         let address = self.next_word();
         let value = selector(&self.cpu.state);
-        self.ram.write_u8(address, value);
+        self.write_memory(address, value);
         self.clock(13);
     }
 
     pub(crate) fn load_param_memory_into_register(&mut self, selector: fn(&mut State) -> &mut u8) {
         {
             let address = self.next_word();
-            let value = self.ram.read_u8(address);
+            let value = self.read_memory(address);
             let dest = selector(&mut self.cpu.state);
             *dest = value;
         }
@@ -71,7 +71,7 @@ impl Machine {
         {
             let value = source(&self.cpu.state);
             let address = pointer(&self.cpu.state);
-            self.ram.write_u8(address, value);
+            self.write_memory(address, value);
         }
         self.clock(7);
     }
@@ -198,22 +198,28 @@ pub enum Mnemonic {
     JpXX = 0xC3,
     CallNZXX = 0xC4,
     PushBC = 0xC5,
+    Rst00 = 0xC7,
     RetZ = 0xC8,
     Ret = 0xC9,
     JpZXX = 0xCA,
     BITS = 0xCB,
     CallZXX = 0xCC,
     CallXX = 0xCD,
+    Rst08 = 0xCF,
 
     RetNC = 0xD0,
     PopDE = 0xD1,
     JpNCXX = 0xD2,
+    OutVXA = 0xD3,
     CallNCXX = 0xD4,
     PushDE = 0xD5,
+    Rst10 = 0xD7,
     RetC = 0xD8,
     Exx = 0xD9,
     JpCXX = 0xDA,
+    InAVX = 0xDB,
     CallCXX = 0xDC,
+    Rst18 = 0xDF,
 
     RetPO = 0xE0,
     PopHL = 0xE1,
@@ -222,21 +228,27 @@ pub enum Mnemonic {
     CallPOXX = 0xE4,
     PushHL = 0xE5,
     AndX = 0xE6,
+    Rst20 = 0xE7,
     RetPE = 0xE8,
     JpPEXX = 0xEA,
     ExDEHL = 0xEB,
     CallPEXX = 0xEC,
     XorX = 0xEE,
+    Rst28 = 0xEF,
 
     RetP = 0xF0,
     PopAF = 0xF1,
     JpPXX = 0xF2,
+    Di = 0xF3,
     CallPXX = 0xF4,
     PushAF = 0xF5,
     OrX = 0xF6,
+    Rst30 = 0xF7,
     RetM = 0xF8,
     JpMXX = 0xFA,
+    Ei = 0xFB,
     CallMXX = 0xFC,
+    Rst38 = 0xFF,
 }
 
 impl From<u8> for Mnemonic {
@@ -5,6 +5,7 @@ mod bitwise;
 mod call_return;
 mod control;
 mod exchange;
+mod input_output;
 mod jump;
 mod load_16bit;
 mod load_8bit;
@@ -39,6 +40,8 @@ impl Machine {
             Mnemonic::CCF => self.complement_carry_flag(),
             Mnemonic::CPL => self.complement_registers(Registers::into_a()),
             Mnemonic::Halt => self.halt(),
+            Mnemonic::Di => self.disable_interrupts(),
+            Mnemonic::Ei => self.enable_interrupts(),
 
             Mnemonic::Exx => self.shadow_exchange_bc_de_hl(),
             Mnemonic::ExAFAF => self.shadow_exchange_af(),
@@ -141,6 +144,15 @@ impl Machine {
             Mnemonic::CallPXX => self.call(|status| !Flag::Sign.get(status)),
             Mnemonic::CallMXX => self.call(|status| Flag::Sign.get(status)),
 
+            Mnemonic::Rst00 => self.restart(0x0000),
+            Mnemonic::Rst08 => self.restart(0x0008),
+            Mnemonic::Rst10 => self.restart(0x0010),
+            Mnemonic::Rst18 => self.restart(0x0018),
+            Mnemonic::Rst20 => self.restart(0x0020),
+            Mnemonic::Rst28 => self.restart(0x0028),
+            Mnemonic::Rst30 => self.restart(0x0030),
+            Mnemonic::Rst38 => self.restart(0x0038),
+
             Mnemonic::Ret => self.ret(),
             Mnemonic::RetNZ => self.ret_conditional(|status| !Flag::Zero.get(status)),
             Mnemonic::RetZ => self.ret_conditional(|status| Flag::Zero.get(status)),
@@ -301,6 +313,9 @@ impl Machine {
             Mnemonic::PopDE => self.pop_from_stack(Registers::into_de()),
             Mnemonic::PopHL => self.pop_from_stack(Registers::into_hl()),
 
+            Mnemonic::InAVX => self.input_from_port_into_accumulator(),
+            Mnemonic::OutVXA => self.output_accumulator_to_port(),
+
             Mnemonic::RLCA => self.rotate_accumulator_copy_left(),
             Mnemonic::RRCA => self.rotate_accumulator_copy_right(),
             Mnemonic::RLA => self.rotate_accumulator_left(),
@@ -313,7 +328,7 @@ impl Machine {
 
     fn next_byte(&mut self) -> u8 {
         let pc = alu::get_word(self.cpu.state.pc);
-        let val = self.ram.read_u8(pc);
+        let val = self.read_memory(pc);
         let (result, overflow) = pc.overflowing_add(1);
         if overflow {
             self.stop();
@@ -335,7 +350,7 @@ impl Machine {
         (high << 8) | low
     }
 
-    pub fn clock(&mut self, _tstates: u8) {
-        // TODO: Something.
+    pub fn clock(&mut self, tstates: u8) {
+        self.cycles += tstates as u64;
     }
 }
@@ -31,7 +31,7 @@ impl Machine {
     pub(crate) fn rotate_accumulator_left(&mut self) {
         let old_value = {
             let a = self.cpu.state.registers.af.0 as u16;
-            let carry = Flag::Carry.get_bit(&mut self.cpu.state) as u16;
+            let carry = Flag::Carry.get_bit(&self.cpu.state) as u16;
             carry << 8 | a
         };
 
@@ -60,7 +60,7 @@ impl Machine {
     pub(crate) fn rotate_accumulator_right(&mut self) {
         let old_value = {
             let a = self.cpu.state.registers.af.0 as u16;
-            let carry = Flag::Carry.get_bit(&mut self.cpu.state) as u16;
+            let carry = Flag::Carry.get_bit(&self.cpu.state) as u16;
             a << 8 | carry << 7
         };
 
@@ -0,0 +1,46 @@
+use crate::vm::machine::Machine;
+
+const INTERRUPT_VECTOR: u16 = 0x0038;
+const NMI_VECTOR: u16 = 0x0066;
+
+impl Machine {
+    pub(crate) fn service_interrupts(&mut self) {
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            self.cpu.unhalt();
+            self.cpu.accept_nmi();
+            self.push_program_counter_to_stack();
+            self.cpu.goto(NMI_VECTOR);
+            self.clock(11);
+            return;
+        }
+        let accepted = self.cpu.accepts_interrupt();
+        if accepted && self.vdp.interrupt_pending() {
+            self.cpu.unhalt();
+            self.cpu.disable_interrupts();
+            self.push_program_counter_to_stack();
+            self.cpu.goto(INTERRUPT_VECTOR);
+            self.clock(13);
+        }
+    }
+
+    pub fn nmi_pending(&self) -> bool {
+        self.nmi_pending
+    }
+
+    // The NMI line is edge triggered, so holding the button down only interrupts once.
+    pub fn set_pause_button(&mut self, pressed: bool) {
+        if self.game_gear_mode() {
+            return;
+        }
+        if pressed && !self.pause_button {
+            self.nmi_pending = true;
+        }
+        self.pause_button = pressed;
+    }
+
+    pub fn press_pause(&mut self) {
+        self.set_pause_button(true);
+        self.set_pause_button(false);
+    }
+}
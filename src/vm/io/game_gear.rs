@@ -0,0 +1,64 @@
+use crate::vm::io::Region;
+use crate::vm::machine::Machine;
+use crate::vm::system::System;
+use crate::vm::vdp::{TvSystem, VdpModel};
+
+pub const GAME_GEAR_PORT_COUNT: usize = 7;
+pub const GAME_GEAR_PORT_DEFAULTS: [u8; GAME_GEAR_PORT_COUNT] =
+    [0xC0, 0x7F, 0xFF, 0x00, 0xFF, 0x00, 0xFF];
+
+impl Machine {
+    // A Game Gear running a Master System cartridge hides its own ports and stereo control.
+    pub(crate) fn game_gear_mode(&self) -> bool {
+        self.vdp.model() == VdpModel::GameGear && self.system != System::MasterGear
+    }
+
+    pub fn start_button(&self) -> bool {
+        self.start_button
+    }
+
+    // In Master System mode Start stands in for the Pause button.
+    pub fn set_start_button(&mut self, pressed: bool) {
+        self.start_button = pressed;
+        if self.system == System::MasterGear {
+            self.set_pause_button(pressed);
+        }
+    }
+
+    // Port 0x00 reports Start on bit 7 (active low), the export region on bit 6 and PAL on bit 5.
+    pub(crate) fn read_game_gear_port(&mut self, port: u8) -> u8 {
+        match port {
+            0x00 => {
+                let start = if self.start_button { 0x00 } else { 0x80 };
+                let export = if self.region == Region::Japan {
+                    0x00
+                } else {
+                    0x40
+                };
+                let pal = if self.tv_system() == TvSystem::Pal {
+                    0x20
+                } else {
+                    0x00
+                };
+                start | export | pal
+            }
+            0x01 => self.read_parallel(),
+            0x04 => self.read_serial_data(),
+            0x05 => self.read_serial_control(),
+            _ => self.game_gear_ports[port as usize],
+        }
+    }
+
+    pub(crate) fn write_game_gear_port(&mut self, port: u8, value: u8) {
+        match port {
+            0x01 | 0x02 => self.write_parallel(port, value),
+            0x03 => self.write_serial_data(value),
+            0x05 => self.write_serial_control(value),
+            0x06 => {
+                self.game_gear_ports[port as usize] = value;
+                self.write_psg_stereo(value);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::vm::io::peripheral::{Peripheral, PORT_COUNT};
+use crate::vm::io::Region;
+use crate::vm::machine::Machine;
+
+pub const IO_CONTROL_RESET: u8 = 0xFF;
+
+impl Machine {
+    pub fn io_control(&self) -> u8 {
+        self.io_control
+    }
+
+    // Port 0x3F: bits 0-3 are the TR A, TH A, TR B and TH B directions (1 for input) and bits
+    // 4-7 the levels they drive as outputs.
+    pub(crate) fn write_io_control(&mut self, value: u8) {
+        let previous: Vec<bool> = (0..PORT_COUNT).map(|port| self.th_pin(port)).collect();
+        self.io_control = value;
+        let h_counter = self.vdp.current_h_counter();
+        for (port, previous) in previous.into_iter().enumerate() {
+            self.th_changed(port, previous, h_counter);
+        }
+    }
+
+    // TH falling from high to low latches the H counter, whether the pin is driven through
+    // port 0x3F or by a Light Phaser. `h_counter` is the counter at the moment it fell.
+    pub(crate) fn th_changed(&mut self, port: usize, previous: bool, h_counter: u8) {
+        let current = self.th_pin(port);
+        if current == previous {
+            return;
+        }
+        if !current {
+            self.vdp.latch_h_counter(h_counter);
+        }
+        if self.peripherals[port] == Peripheral::SportsPad {
+            self.sports_pad_th_changed(port, current);
+        }
+    }
+
+    pub(crate) fn th_output(&self, port: usize) -> Option<bool> {
+        self.pin_output(port, 0x02)
+    }
+
+    pub(crate) fn tr_output(&self, port: usize) -> Option<bool> {
+        self.pin_output(port, 0x01)
+    }
+
+    // Japanese consoles return the inverse of an output level, which is how games tell them
+    // apart from export consoles.
+    pub(crate) fn th_level(&self, port: usize) -> u8 {
+        match self.th_output(port) {
+            Some(level) => self.read_back(level),
+            None => self.th_line(port) as u8,
+        }
+    }
+
+    pub(crate) fn tr_level(&self, port: usize, input: bool) -> u8 {
+        match self.tr_output(port) {
+            Some(level) => self.read_back(level),
+            None => input as u8,
+        }
+    }
+
+    fn read_back(&self, level: bool) -> u8 {
+        (level != (self.region == Region::Japan)) as u8
+    }
+
+    pub(crate) fn th_pin(&self, port: usize) -> bool {
+        self.th_output(port).unwrap_or_else(|| self.th_line(port))
+    }
+
+    fn pin_output(&self, port: usize, pin: u8) -> Option<bool> {
+        let direction = pin << (port * 2);
+        let level = direction << 4;
+        if self.io_control & direction == 0 {
+            Some(self.io_control & level != 0)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::vm::machine::Machine;
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Joypad {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub button1: bool,
+    pub button2: bool,
+}
+
+impl Joypad {
+    pub fn new() -> Joypad {
+        Joypad::default()
+    }
+
+    // Active-low line levels in the order up, down, left, right, TL (button 1), TR (button 2).
+    pub fn lines(self) -> u8 {
+        let pressed = [
+            self.up,
+            self.down,
+            self.left,
+            self.right,
+            self.button1,
+            self.button2,
+        ];
+        pressed.iter().enumerate().fold(
+            0x3F,
+            |lines, (bit, pressed)| {
+                if *pressed {
+                    lines & !(1 << bit)
+                } else {
+                    lines
+                }
+            },
+        )
+    }
+}
+
+impl Machine {
+    pub fn joypad(&self, player: usize) -> Joypad {
+        self.joypads[player]
+    }
+
+    pub fn set_joypad(&mut self, player: usize, state: Joypad) {
+        self.joypads[player] = state;
+    }
+
+    pub fn set_joypads(&mut self, player1: Joypad, player2: Joypad) {
+        self.joypads = [player1, player2];
+    }
+
+    pub fn reset_button(&self) -> bool {
+        self.reset_button
+    }
+
+    pub fn set_reset_button(&mut self, pressed: bool) {
+        self.reset_button = pressed;
+        self.reset_tapped = false;
+    }
+}
@@ -0,0 +1,188 @@
+use crate::vm::machine::Machine;
+
+pub const KEYBOARD_ROWS: usize = 8;
+
+// Keys of the SC-3000 matrix, named after their unshifted legends.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Key {
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    Digit0,
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Minus,
+    Caret,
+    Yen,
+    At,
+    LeftBracket,
+    RightBracket,
+    Semicolon,
+    Colon,
+    Comma,
+    Period,
+    Slash,
+    Pi,
+    Space,
+    Return,
+    Up,
+    Down,
+    Left,
+    Right,
+    HomeClear,
+    InsertDelete,
+    EngDiers,
+    Break,
+    Graph,
+    Func,
+    Ctrl,
+    Shift,
+}
+
+impl Key {
+    // Rows are selected through PPI port C; columns 0-7 are read on port A and 8-11 on port B.
+    pub fn position(self) -> (usize, usize) {
+        match self {
+            Key::Digit1 => (0, 0),
+            Key::Q => (0, 1),
+            Key::A => (0, 2),
+            Key::Z => (0, 3),
+            Key::EngDiers => (0, 4),
+            Key::Comma => (0, 5),
+            Key::K => (0, 6),
+            Key::I => (0, 7),
+            Key::Digit8 => (0, 8),
+            Key::Digit2 => (1, 0),
+            Key::W => (1, 1),
+            Key::S => (1, 2),
+            Key::X => (1, 3),
+            Key::Space => (1, 4),
+            Key::Period => (1, 5),
+            Key::L => (1, 6),
+            Key::O => (1, 7),
+            Key::Digit9 => (1, 8),
+            Key::Digit3 => (2, 0),
+            Key::E => (2, 1),
+            Key::D => (2, 2),
+            Key::C => (2, 3),
+            Key::HomeClear => (2, 4),
+            Key::Slash => (2, 5),
+            Key::Semicolon => (2, 6),
+            Key::P => (2, 7),
+            Key::Digit0 => (2, 8),
+            Key::Digit4 => (3, 0),
+            Key::R => (3, 1),
+            Key::F => (3, 2),
+            Key::V => (3, 3),
+            Key::InsertDelete => (3, 4),
+            Key::Pi => (3, 5),
+            Key::Colon => (3, 6),
+            Key::At => (3, 7),
+            Key::Minus => (3, 8),
+            Key::Digit5 => (4, 0),
+            Key::T => (4, 1),
+            Key::G => (4, 2),
+            Key::B => (4, 3),
+            Key::Down => (4, 5),
+            Key::RightBracket => (4, 6),
+            Key::LeftBracket => (4, 7),
+            Key::Caret => (4, 8),
+            Key::Digit6 => (5, 0),
+            Key::Y => (5, 1),
+            Key::H => (5, 2),
+            Key::N => (5, 3),
+            Key::Left => (5, 5),
+            Key::Return => (5, 6),
+            Key::Yen => (5, 8),
+            Key::Func => (5, 11),
+            Key::Digit7 => (6, 0),
+            Key::U => (6, 1),
+            Key::J => (6, 2),
+            Key::M => (6, 3),
+            Key::Right => (6, 5),
+            Key::Up => (6, 6),
+            Key::Break => (6, 8),
+            Key::Graph => (6, 9),
+            Key::Ctrl => (6, 10),
+            Key::Shift => (6, 11),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Keyboard {
+    rows: [u16; KEYBOARD_ROWS],
+}
+
+impl Keyboard {
+    pub fn new() -> Keyboard {
+        Keyboard::default()
+    }
+
+    pub fn is_pressed(&self, key: Key) -> bool {
+        let (row, column) = key.position();
+        self.rows[row] & (1 << column) != 0
+    }
+
+    pub fn set_pressed(&mut self, key: Key, pressed: bool) {
+        let (row, column) = key.position();
+        if pressed {
+            self.rows[row] |= 1 << column;
+        } else {
+            self.rows[row] &= !(1 << column);
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.rows = [0; KEYBOARD_ROWS];
+    }
+
+    // Active-low column lines for a row.
+    pub fn columns(&self, row: usize) -> u16 {
+        !self.rows[row] & 0x0FFF
+    }
+}
+
+impl Machine {
+    pub fn key_pressed(&self, key: Key) -> bool {
+        self.keyboard.is_pressed(key)
+    }
+
+    pub fn set_key(&mut self, key: Key, pressed: bool) {
+        self.keyboard.set_pressed(key, pressed);
+    }
+
+    pub fn release_keys(&mut self) {
+        self.keyboard.release_all();
+    }
+}
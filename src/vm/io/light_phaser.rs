@@ -0,0 +1,86 @@
+use crate::vm::io::peripheral::{Peripheral, PORT_COUNT};
+use crate::vm::machine::Machine;
+use crate::vm::vdp::framebuffer::Framebuffer;
+use crate::vm::vdp::Vdp;
+
+const SENSOR_RADIUS: usize = 4;
+const BRIGHTNESS_THRESHOLD: u32 = 0x80;
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct LightPhaser {
+    pub x: usize,
+    pub y: usize,
+    pub trigger: bool,
+}
+
+impl LightPhaser {
+    pub fn new() -> LightPhaser {
+        LightPhaser::default()
+    }
+
+    pub fn lines(self) -> u8 {
+        if self.trigger {
+            0x2F
+        } else {
+            0x3F
+        }
+    }
+
+    pub fn covers(self, line: usize) -> bool {
+        line + SENSOR_RADIUS >= self.y && line <= self.y + SENSOR_RADIUS
+    }
+
+    // Returns the leftmost bright pixel the sensor sees among the first `drawn` pixels of a
+    // line, which is where the beam first lights it up.
+    pub fn sense(self, framebuffer: &Framebuffer, line: usize, drawn: usize) -> Option<usize> {
+        if !self.covers(line) {
+            return None;
+        }
+        let start = self.x.saturating_sub(SENSOR_RADIUS);
+        let end = (self.x + SENSOR_RADIUS + 1)
+            .min(framebuffer.width())
+            .min(drawn);
+        (start..end).find(|x| brightness(framebuffer.pixel(*x, line)) >= BRIGHTNESS_THRESHOLD)
+    }
+}
+
+fn brightness(pixel: u32) -> u32 {
+    let red = (pixel >> 16) & 0xFF;
+    let green = (pixel >> 8) & 0xFF;
+    let blue = pixel & 0xFF;
+    (red * 299 + green * 587 + blue * 114) / 1000
+}
+
+impl Machine {
+    pub fn light_phaser(&self, port: usize) -> LightPhaser {
+        self.light_phasers[port]
+    }
+
+    pub fn set_light_phaser(&mut self, port: usize, state: LightPhaser) {
+        self.light_phasers[port] = state;
+    }
+
+    // The sensor sees the current frame as the beam draws it. TH falls once the beam passes a
+    // lit spot and stays low for the rest of the line, latching the H counter at the spot's
+    // position. Needs the VDP synchronized to the current cycle.
+    pub(crate) fn sense_light_phasers(&mut self) {
+        let line = self.vdp.line() as usize;
+        let drawn = self.vdp.beam_x();
+        for port in 0..PORT_COUNT {
+            if self.peripherals[port] != Peripheral::LightPhaser {
+                continue;
+            }
+            let phaser = self.light_phasers[port];
+            let hit = if line < self.vdp.active_height() && phaser.covers(line) {
+                self.vdp.render_span(drawn);
+                phaser.sense(self.vdp.framebuffer(), line, drawn)
+            } else {
+                None
+            };
+            let previous = self.th_pin(port);
+            self.light_sensed[port] = hit.is_some();
+            let h_counter = hit.map_or(0, Vdp::h_counter_at);
+            self.th_changed(port, previous, h_counter);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::vm::machine::Machine;
+
+pub const PARALLEL_IDLE: u8 = 0x7F;
+
+const PARALLEL_NMI_ENABLE: u8 = 0x80;
+const PARALLEL_INTERRUPT_PIN: u8 = 0x40;
+
+const SERIAL_TRANSMIT_FULL: u8 = 0x01;
+const SERIAL_RECEIVE_READY: u8 = 0x02;
+const SERIAL_FRAMING_ERROR: u8 = 0x04;
+const SERIAL_NMI_ENABLE: u8 = 0x08;
+const SERIAL_TRANSMIT_ENABLE: u8 = 0x10;
+const SERIAL_RECEIVE_ENABLE: u8 = 0x20;
+const SERIAL_STATUS: u8 = 0x07;
+
+// Ten bits per byte (start, eight data, stop) at 4800, 2400, 1200 or 300 baud.
+const BYTE_CYCLES: [u64; 4] = [7_457, 14_915, 29_830, 119_318];
+
+#[derive(Clone, Debug)]
+struct LinkEnd {
+    parallel: u8,
+    incoming: VecDeque<(u64, u8)>,
+}
+
+impl LinkEnd {
+    fn new() -> LinkEnd {
+        LinkEnd {
+            parallel: PARALLEL_IDLE,
+            incoming: VecDeque::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LinkCable {
+    ends: [LinkEnd; 2],
+}
+
+impl Default for LinkCable {
+    fn default() -> Self {
+        LinkCable::new()
+    }
+}
+
+impl LinkCable {
+    pub fn new() -> LinkCable {
+        LinkCable {
+            ends: [LinkEnd::new(), LinkEnd::new()],
+        }
+    }
+
+    // Connects two machines; bytes are timestamped with the sender's cycle count, so running
+    // both machines in lockstep gives the same result every time.
+    pub fn connect(first: &mut Machine, second: &mut Machine) -> Rc<RefCell<LinkCable>> {
+        let cable = Rc::new(RefCell::new(LinkCable::new()));
+        first.attach_link(cable.clone(), 0);
+        second.attach_link(cable.clone(), 1);
+        cable
+    }
+
+    pub fn parallel(&self, side: usize) -> u8 {
+        self.ends[side].parallel
+    }
+
+    pub fn bytes_in_transit(&self, side: usize) -> usize {
+        self.ends[side].incoming.len()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct LinkPort {
+    cable: Rc<RefCell<LinkCable>>,
+    side: usize,
+}
+
+impl LinkPort {
+    fn peer(&self) -> usize {
+        1 - self.side
+    }
+}
+
+impl Machine {
+    pub fn is_linked(&self) -> bool {
+        self.link.is_some()
+    }
+
+    pub fn disconnect_link(&mut self) {
+        if let Some(link) = self.link.take() {
+            let mut cable = link.cable.borrow_mut();
+            cable.ends[link.side] = LinkEnd::new();
+        }
+        self.link_lines = PARALLEL_IDLE;
+    }
+
+    fn attach_link(&mut self, cable: Rc<RefCell<LinkCable>>, side: usize) {
+        self.link = Some(LinkPort { cable, side });
+        self.publish_parallel();
+    }
+
+    // Input pins are pulled high, so only pins configured as outputs drive the cable.
+    fn parallel_output(&self) -> u8 {
+        (self.game_gear_ports[0x01] | self.game_gear_ports[0x02]) & PARALLEL_IDLE
+    }
+
+    fn parallel_input(&self) -> u8 {
+        match self.link.as_ref() {
+            Some(link) => link.cable.borrow().ends[link.peer()].parallel,
+            None => PARALLEL_IDLE,
+        }
+    }
+
+    fn publish_parallel(&mut self) {
+        let output = self.parallel_output();
+        if let Some(link) = self.link.as_ref() {
+            link.cable.borrow_mut().ends[link.side].parallel = output;
+        }
+    }
+
+    pub(crate) fn read_parallel(&mut self) -> u8 {
+        self.poll_link();
+        let direction = self.game_gear_ports[0x02] & PARALLEL_IDLE;
+        let data = self.game_gear_ports[0x01];
+        (data & !direction) | (self.parallel_input() & direction)
+    }
+
+    pub(crate) fn write_parallel(&mut self, port: u8, value: u8) {
+        self.game_gear_ports[port as usize] = value;
+        self.publish_parallel();
+    }
+
+    pub(crate) fn read_serial_data(&mut self) -> u8 {
+        self.poll_link();
+        self.game_gear_ports[0x05] &= !(SERIAL_RECEIVE_READY | SERIAL_FRAMING_ERROR);
+        self.game_gear_ports[0x04]
+    }
+
+    pub(crate) fn read_serial_control(&mut self) -> u8 {
+        self.poll_link();
+        if self.cycles >= self.serial_transmit_end {
+            self.game_gear_ports[0x05] &= !SERIAL_TRANSMIT_FULL;
+        }
+        self.game_gear_ports[0x05]
+    }
+
+    pub(crate) fn write_serial_control(&mut self, value: u8) {
+        let status = self.game_gear_ports[0x05] & SERIAL_STATUS;
+        self.game_gear_ports[0x05] = (value & !SERIAL_STATUS) | status;
+    }
+
+    pub(crate) fn write_serial_data(&mut self, value: u8) {
+        self.game_gear_ports[0x03] = value;
+        let control = self.game_gear_ports[0x05];
+        if control & SERIAL_TRANSMIT_ENABLE == 0 {
+            return;
+        }
+        let start = self.cycles.max(self.serial_transmit_end);
+        self.serial_transmit_end = start + BYTE_CYCLES[(control >> 6) as usize];
+        self.game_gear_ports[0x05] |= SERIAL_TRANSMIT_FULL;
+        if let Some(link) = self.link.as_ref() {
+            let mut cable = link.cable.borrow_mut();
+            let peer = link.peer();
+            cable.ends[peer]
+                .incoming
+                .push_back((self.serial_transmit_end, value));
+        }
+    }
+
+    // Called every line and on link port reads to pick up parallel edges and arrived bytes.
+    pub(crate) fn poll_link(&mut self) {
+        let link = match self.link.as_ref() {
+            Some(link) => link.clone(),
+            None => return,
+        };
+        let lines = self.parallel_input();
+        let falling = self.link_lines & !lines & PARALLEL_INTERRUPT_PIN != 0;
+        let input = self.game_gear_ports[0x02] & PARALLEL_INTERRUPT_PIN != 0;
+        if falling && input && self.game_gear_ports[0x02] & PARALLEL_NMI_ENABLE != 0 {
+            self.nmi_pending = true;
+        }
+        self.link_lines = lines;
+
+        let mut cable = link.cable.borrow_mut();
+        let incoming = &mut cable.ends[link.side].incoming;
+        while let Some((arrival, value)) = incoming.front().cloned() {
+            if arrival > self.cycles {
+                break;
+            }
+            incoming.pop_front();
+            let control = self.game_gear_ports[0x05];
+            if control & SERIAL_RECEIVE_ENABLE == 0 {
+                continue;
+            }
+            if control & SERIAL_RECEIVE_READY != 0 {
+                self.game_gear_ports[0x05] |= SERIAL_FRAMING_ERROR;
+            }
+            self.game_gear_ports[0x04] = value;
+            self.game_gear_ports[0x05] |= SERIAL_RECEIVE_READY;
+            if control & SERIAL_NMI_ENABLE != 0 {
+                self.nmi_pending = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::vm::machine::Machine;
+use crate::vm::ram::MemoryMap;
+
+// Values the BIOS leaves in port 0x3E: with a BIOS only the BIOS is mapped, otherwise the
+// cartridge slot is enabled directly.
+pub const MEMORY_CONTROL_BIOS: u8 = 0xE3;
+pub const MEMORY_CONTROL_CARTRIDGE: u8 = 0xAB;
+
+const CARTRIDGE_DISABLE: u8 = 0x40;
+const BIOS_DISABLE: u8 = 0x08;
+const IO_DISABLE: u8 = 0x04;
+
+impl Machine {
+    pub fn memory_control(&self) -> u8 {
+        self.memory_control
+    }
+
+    pub(crate) fn io_enabled(&self) -> bool {
+        self.memory_control & IO_DISABLE == 0
+    }
+
+    pub(crate) fn write_memory_control(&mut self, value: u8) {
+        let changed = (self.memory_control ^ value) & (CARTRIDGE_DISABLE | BIOS_DISABLE) != 0;
+        self.memory_control = value;
+        if changed {
+            self.map_slots();
+        }
+    }
+
+    // Copies whichever image is enabled into the ROM area; the BIOS wins if both are. With
+    // images present but none enabled, the ROM area reads as open bus.
+    pub(crate) fn map_slots(&mut self) {
+        if let Some(image) = self.mapped_image() {
+            let mut rom = if self.is_paged(image.len()) {
+                self.paged_rom(image)
+            } else {
+                image.clone()
+            };
+            if let MemoryMap::Cartridge { rom_end, .. } = self.ram.map() {
+                rom.resize(rom_end as usize, 0xFF);
+            }
+            self.ram.load(0, &rom);
+            self.ram.set_rom_mapped(true);
+        } else {
+            let unmapped = self.bios.is_some() || self.cartridge.is_some();
+            self.ram.set_rom_mapped(!unmapped);
+        }
+    }
+
+    // The image the memory control register leaves in the ROM area, if any.
+    pub(crate) fn mapped_image(&self) -> Option<&Vec<u8>> {
+        if self.memory_control & BIOS_DISABLE == 0 && self.bios.is_some() {
+            self.bios.as_ref()
+        } else if self.memory_control & CARTRIDGE_DISABLE == 0 {
+            self.cartridge.as_ref()
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,138 @@
+pub mod game_gear;
+pub mod io_control;
+pub mod joypad;
+pub mod keyboard;
+pub mod light_phaser;
+pub mod link;
+pub mod memory_control;
+pub mod open_bus;
+pub mod paddle;
+pub mod peripheral;
+pub mod ppi;
+pub mod sg1000;
+pub mod sports_pad;
+
+use crate::vm::machine::Machine;
+use crate::vm::scheduler::CYCLES_PER_LINE;
+use crate::vm::vdp::TvSystem;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Region {
+    Japan,
+    ExportNtsc,
+    ExportPal,
+}
+
+impl Region {
+    pub fn tv_system(self) -> TvSystem {
+        match self {
+            Region::ExportPal => TvSystem::Pal,
+            _ => TvSystem::Ntsc,
+        }
+    }
+}
+
+impl Machine {
+    pub fn read_port(&mut self, port: u8) -> u8 {
+        self.synchronize_vdp();
+        self.sense_light_phasers();
+        match self.decode_read(port) {
+            Some(value) => value,
+            None => self.read_unmapped_port(port),
+        }
+    }
+
+    pub fn write_port(&mut self, port: u8, value: u8) {
+        self.synchronize_vdp();
+        self.sense_light_phasers();
+        if !self.decode_write(port, value) {
+            self.write_unmapped_port(port, value);
+        }
+    }
+
+    fn decode_read(&mut self, port: u8) -> Option<u8> {
+        if !self.system.has_io() {
+            return None;
+        }
+        if self.system.has_sg1000_io() {
+            return self.read_sg1000_port(port);
+        }
+        if self.fm.is_some() && port == 0xF2 {
+            return Some(self.audio_control);
+        }
+        if self.game_gear_mode() && port <= 0x06 {
+            return Some(self.read_game_gear_port(port));
+        }
+        match port & 0xC1 {
+            0x40 => Some(self.vdp.read_v_counter()),
+            0x41 => Some(self.vdp.read_h_counter()),
+            0x80 => Some(self.vdp.read_data()),
+            0x81 => Some(self.vdp.read_control()),
+            0xC0 if self.io_enabled() => Some(self.read_controller_port_a()),
+            0xC1 if self.io_enabled() => Some(self.read_controller_port_b()),
+            _ => None,
+        }
+    }
+
+    fn decode_write(&mut self, port: u8, value: u8) -> bool {
+        if !self.system.has_io() {
+            return false;
+        }
+        if self.system.has_sg1000_io() {
+            return self.write_sg1000_port(port, value);
+        }
+        if let Some(fm) = self.fm.as_mut() {
+            match port {
+                0xF0 => fm.write_address(value),
+                0xF1 => self.write_fm_data(value),
+                0xF2 => self.write_audio_control(value),
+                _ => {}
+            }
+            if (0xF0..=0xF2).contains(&port) {
+                return true;
+            }
+        }
+        if self.game_gear_mode() && port <= 0x06 {
+            self.write_game_gear_port(port, value);
+            return true;
+        }
+        match port & 0xC1 {
+            0x00 => self.write_memory_control(value),
+            0x01 => self.write_io_control(value),
+            0x40 | 0x41 => self.write_psg(value),
+            0x80 => self.vdp.write_data(value),
+            0x81 => self.vdp.write_control(value),
+            _ => return false,
+        }
+        true
+    }
+
+    pub(crate) fn write_psg(&mut self, value: u8) {
+        if let Some(recorder) = self.vgm.as_mut() {
+            recorder.psg(self.cycles, value);
+        }
+        self.psg.write(self.cycles, value, &mut self.audio);
+    }
+
+    pub(crate) fn write_psg_stereo(&mut self, value: u8) {
+        if let Some(recorder) = self.vgm.as_mut() {
+            recorder.game_gear_stereo(self.cycles, value);
+        }
+        self.psg.write_stereo(self.cycles, value, &mut self.audio);
+    }
+
+    fn write_fm_data(&mut self, value: u8) {
+        if let Some(fm) = self.fm.as_mut() {
+            if let Some(recorder) = self.vgm.as_mut() {
+                recorder.ym2413(self.cycles, fm.address(), value);
+            }
+            fm.write_data(self.cycles, value, &mut self.audio);
+        }
+    }
+
+    pub(crate) fn synchronize_vdp(&mut self) {
+        let line_start = self.line_end.saturating_sub(CYCLES_PER_LINE);
+        let line_cycle = self.cycles.saturating_sub(line_start);
+        self.vdp.synchronize(self.cycles, line_cycle);
+    }
+}
@@ -0,0 +1,47 @@
+use crate::vm::machine::Machine;
+use crate::vm::vdp::VdpModel;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PortAccess {
+    Read(u8),
+    Write(u8, u8),
+}
+
+pub type PortCallback = Box<dyn FnMut(u64, PortAccess)>;
+
+impl Machine {
+    pub fn data_bus(&self) -> u8 {
+        self.data_bus
+    }
+
+    // The Mark III, SMS1 and SG-1000 leave the data bus floating on unmapped reads, so the
+    // last byte transferred (usually the port operand of `in a, (n)`) is read back; later
+    // consoles pull it up to 0xFF.
+    pub fn open_bus(&self) -> u8 {
+        match self.vdp.model() {
+            VdpModel::Sms1 | VdpModel::Tms9918a if self.system.has_io() => self.data_bus,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn set_unmapped_port_callback(&mut self, callback: PortCallback) {
+        self.unmapped_port_callback = Some(callback);
+    }
+
+    pub fn take_unmapped_port_callback(&mut self) -> Option<PortCallback> {
+        self.unmapped_port_callback.take()
+    }
+
+    pub(crate) fn read_unmapped_port(&mut self, port: u8) -> u8 {
+        if let Some(callback) = self.unmapped_port_callback.as_mut() {
+            callback(self.cycles, PortAccess::Read(port));
+        }
+        self.open_bus()
+    }
+
+    pub(crate) fn write_unmapped_port(&mut self, port: u8, value: u8) {
+        if let Some(callback) = self.unmapped_port_callback.as_mut() {
+            callback(self.cycles, PortAccess::Write(port, value));
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::vm::io::Region;
+use crate::vm::machine::Machine;
+
+// The Japanese paddle switches nibbles on its own at roughly 8 kHz.
+const TOGGLE_CYCLES: u64 = 224;
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Paddle {
+    pub position: u8,
+    pub button: bool,
+}
+
+impl Paddle {
+    pub fn new() -> Paddle {
+        Paddle::default()
+    }
+
+    // TR reads 0 with the low nibble and 1 with the high nibble.
+    pub fn lines(self, high_nibble: bool) -> u8 {
+        let nibble = if high_nibble {
+            self.position >> 4
+        } else {
+            self.position & 0x0F
+        };
+        let button = if self.button { 0x00 } else { 0x10 };
+        let select = if high_nibble { 0x20 } else { 0x00 };
+        nibble | button | select
+    }
+}
+
+impl Machine {
+    pub fn paddle(&self, port: usize) -> Paddle {
+        self.paddles[port]
+    }
+
+    pub fn set_paddle(&mut self, port: usize, state: Paddle) {
+        self.paddles[port] = state;
+    }
+
+    // Export consoles select the nibble by driving TH, low for the high nibble.
+    pub(crate) fn paddle_lines(&self, port: usize) -> u8 {
+        let high_nibble = match self.region {
+            Region::Japan => (self.cycles / TOGGLE_CYCLES) & 1 != 0,
+            _ => self.th_output(port) == Some(false),
+        };
+        self.paddles[port].lines(high_nibble)
+    }
+}
@@ -0,0 +1,57 @@
+use crate::vm::machine::Machine;
+
+pub const PORT_COUNT: usize = 2;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Peripheral {
+    Joypad,
+    LightPhaser,
+    Paddle,
+    SportsPad,
+}
+
+impl Machine {
+    pub fn peripheral(&self, port: usize) -> Peripheral {
+        self.peripherals[port]
+    }
+
+    pub fn set_peripheral(&mut self, port: usize, peripheral: Peripheral) {
+        self.peripherals[port] = peripheral;
+        self.light_sensed[port] = false;
+    }
+
+    // The six active-low lines up, down, left, right, TL and TR of a controller port.
+    fn controller_lines(&self, port: usize) -> u8 {
+        match self.peripherals[port] {
+            Peripheral::Joypad => self.joypads[port].lines(),
+            Peripheral::LightPhaser => self.light_phasers[port].lines(),
+            Peripheral::Paddle => self.paddle_lines(port),
+            Peripheral::SportsPad => self.sports_pad_lines(port),
+        }
+    }
+
+    pub(crate) fn th_line(&self, port: usize) -> bool {
+        match self.peripherals[port] {
+            Peripheral::LightPhaser => !self.light_sensed[port],
+            _ => true,
+        }
+    }
+
+    // Port 0xDC holds all of port A and the up and down lines of port B.
+    pub(crate) fn read_controller_port_a(&self) -> u8 {
+        let port_a = self.controller_lines(0);
+        let port_b = self.controller_lines(1);
+        let tr_a = self.tr_level(0, port_a & 0x20 != 0);
+        (port_a & 0x1F) | tr_a << 5 | (port_b & 0x03) << 6
+    }
+
+    // Port 0xDD holds the rest of port B, the reset button and the TH lines of both ports.
+    pub(crate) fn read_controller_port_b(&self) -> u8 {
+        let port_b = self.controller_lines(1);
+        let tr_b = self.tr_level(1, port_b & 0x20 != 0);
+        let reset = if self.reset_button { 0x00 } else { 0x10 };
+        let th_a = self.th_level(0);
+        let th_b = self.th_level(1);
+        (port_b >> 2 & 0x07) | tr_b << 3 | reset | 0x20 | th_a << 6 | th_b << 7
+    }
+}
@@ -0,0 +1,87 @@
+use crate::vm::io::keyboard::KEYBOARD_ROWS;
+use crate::vm::machine::Machine;
+
+// Mode 0 with ports A and B as inputs and port C as output, as set by the SC-3000 BIOS.
+pub const PPI_CONTROL_RESET: u8 = 0x92;
+
+const JOYPAD_ROW: usize = KEYBOARD_ROWS - 1;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Ppi {
+    control: u8,
+    port_c: u8,
+}
+
+impl Default for Ppi {
+    fn default() -> Self {
+        Ppi::new()
+    }
+}
+
+impl Ppi {
+    pub fn new() -> Ppi {
+        Ppi {
+            control: PPI_CONTROL_RESET,
+            port_c: 0,
+        }
+    }
+
+    pub fn control(&self) -> u8 {
+        self.control
+    }
+
+    pub fn port_c(&self) -> u8 {
+        self.port_c
+    }
+
+    pub fn write_port_c(&mut self, value: u8) {
+        self.port_c = value;
+    }
+
+    // With bit 7 clear, a control write sets or resets a single port C bit instead.
+    pub fn write_control(&mut self, value: u8) {
+        if value & 0x80 != 0 {
+            self.control = value;
+            self.port_c = 0;
+        } else {
+            let bit = 1 << ((value >> 1) & 0x07);
+            if value & 0x01 != 0 {
+                self.port_c |= bit;
+            } else {
+                self.port_c &= !bit;
+            }
+        }
+    }
+
+    pub fn selected_row(&self) -> usize {
+        (self.port_c & 0x07) as usize
+    }
+}
+
+impl Machine {
+    pub(crate) fn read_ppi(&mut self, port: u8) -> u8 {
+        let ppi = match self.ppi {
+            Some(ppi) => ppi,
+            None => return 0xFF,
+        };
+        let row = ppi.selected_row();
+        match port & 0x03 {
+            0 if row == JOYPAD_ROW => self.read_controller_port_a(),
+            1 if row == JOYPAD_ROW => self.read_controller_port_b() | 0xF0,
+            0 => self.keyboard.columns(row) as u8,
+            1 => (self.keyboard.columns(row) >> 8) as u8 | 0xF0,
+            2 => ppi.port_c(),
+            _ => 0xFF,
+        }
+    }
+
+    pub(crate) fn write_ppi(&mut self, port: u8, value: u8) {
+        if let Some(ppi) = self.ppi.as_mut() {
+            match port & 0x03 {
+                2 => ppi.write_port_c(value),
+                3 => ppi.write_control(value),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::vm::machine::Machine;
+
+// The SG-1000 and SC-3000 decode only address lines 6, 7 and 0 (plus 1 for the PPI).
+impl Machine {
+    pub(crate) fn read_sg1000_port(&mut self, port: u8) -> Option<u8> {
+        match port & 0xC1 {
+            0x80 => Some(self.vdp.read_data()),
+            0x81 => Some(self.vdp.read_control()),
+            0xC0 | 0xC1 if self.ppi.is_some() => Some(self.read_ppi(port)),
+            0xC0 => Some(self.read_controller_port_a()),
+            0xC1 => Some(self.read_controller_port_b()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn write_sg1000_port(&mut self, port: u8, value: u8) -> bool {
+        match port & 0xC1 {
+            0x40 | 0x41 => self.write_psg(value),
+            0x80 => self.vdp.write_data(value),
+            0x81 => self.vdp.write_control(value),
+            0xC0 | 0xC1 => self.write_ppi(port, value),
+            _ => return false,
+        }
+        true
+    }
+}
@@ -0,0 +1,63 @@
+use crate::vm::machine::Machine;
+use crate::vm::scheduler::CYCLES_PER_LINE;
+
+// The pad goes back to sending X once TH has been left alone for a few lines.
+const IDLE_CYCLES: u64 = 4 * CYCLES_PER_LINE;
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct SportsPad {
+    pub x: i8,
+    pub y: i8,
+    pub button1: bool,
+    pub button2: bool,
+}
+
+impl SportsPad {
+    pub fn new() -> SportsPad {
+        SportsPad::default()
+    }
+
+    // Nibbles are sent as X high, X low, Y high, Y low.
+    pub fn lines(self, nibble: u8) -> u8 {
+        let value = match nibble & 0x03 {
+            0 => self.x as u8 >> 4,
+            1 => self.x as u8 & 0x0F,
+            2 => self.y as u8 >> 4,
+            _ => self.y as u8 & 0x0F,
+        };
+        let button1 = if self.button1 { 0x00 } else { 0x10 };
+        let button2 = if self.button2 { 0x00 } else { 0x20 };
+        value | button1 | button2
+    }
+}
+
+impl Machine {
+    pub fn sports_pad(&self, port: usize) -> SportsPad {
+        self.sports_pads[port]
+    }
+
+    pub fn set_sports_pad(&mut self, port: usize, state: SportsPad) {
+        self.sports_pads[port] = state;
+    }
+
+    // TH high selects the high nibble and TH low the low one. Each rise moves from X to Y and
+    // back, unless TH has been idle, in which case the pad starts over with X.
+    pub(crate) fn sports_pad_lines(&self, port: usize) -> u8 {
+        let y = self.sports_pad_y[port] && !self.sports_pad_idle(port);
+        let low = !self.th_pin(port);
+        self.sports_pads[port].lines((y as u8) << 1 | low as u8)
+    }
+
+    pub(crate) fn sports_pad_th_changed(&mut self, port: usize, level: bool) {
+        if self.sports_pad_idle(port) {
+            self.sports_pad_y[port] = false;
+        } else if level {
+            self.sports_pad_y[port] = !self.sports_pad_y[port];
+        }
+        self.sports_pad_th_cycles[port] = self.cycles;
+    }
+
+    fn sports_pad_idle(&self, port: usize) -> bool {
+        self.cycles.saturating_sub(self.sports_pad_th_cycles[port]) > IDLE_CYCLES
+    }
+}
@@ -1,34 +1,226 @@
 use crate::program::Program;
+use crate::vm::audio::sink::AudioSink;
+use crate::vm::audio::stereo_buffer::StereoBuffer;
 use crate::vm::callbacks::Callbacks;
 use crate::vm::cpu::alu;
 use crate::vm::cpu::processor::Processor;
 use crate::vm::cpu::state::State;
+use crate::vm::glasses::Eye;
+use crate::vm::io::game_gear::{GAME_GEAR_PORT_COUNT, GAME_GEAR_PORT_DEFAULTS};
+use crate::vm::io::io_control::IO_CONTROL_RESET;
+use crate::vm::io::joypad::Joypad;
+use crate::vm::io::keyboard::Keyboard;
+use crate::vm::io::light_phaser::LightPhaser;
+use crate::vm::io::link::{LinkPort, PARALLEL_IDLE};
+use crate::vm::io::memory_control::MEMORY_CONTROL_CARTRIDGE;
+use crate::vm::io::open_bus::PortCallback;
+use crate::vm::io::paddle::Paddle;
+use crate::vm::io::peripheral::{Peripheral, PORT_COUNT};
+use crate::vm::io::ppi::Ppi;
+use crate::vm::io::sports_pad::SportsPad;
+use crate::vm::io::Region;
+use crate::vm::paging::{PAGING_REGISTER_COUNT, PAGING_RESET};
+use crate::vm::power::PowerOnPattern;
+use crate::vm::psg::Psg;
 use crate::vm::ram::Memory;
+use crate::vm::system::System;
+use crate::vm::vdp::framebuffer::Framebuffer;
+use crate::vm::vdp::{TvSystem, Vdp, VdpModel, Viewport};
+use crate::vm::vgm::recorder::VgmRecorder;
+use crate::vm::ym2413::Ym2413;
+
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
 
 pub struct Machine {
     pub cpu: Processor,
     pub ram: Memory,
+    pub vdp: Vdp,
+    pub psg: Psg,
+    pub fm: Option<Ym2413>,
+    pub audio: StereoBuffer,
+    pub vgm: Option<VgmRecorder>,
+    pub ppi: Option<Ppi>,
+    pub(crate) audio_sink: Option<Box<dyn AudioSink>>,
+    pub(crate) unmapped_port_callback: Option<PortCallback>,
     run: bool,
+    pub(crate) system: System,
+    pub(crate) bios: Option<Vec<u8>>,
+    pub(crate) cartridge: Option<Vec<u8>>,
+    pub(crate) memory_control: u8,
+    pub(crate) paging: [u8; PAGING_REGISTER_COUNT],
+    pub(crate) power_on_pattern: PowerOnPattern,
+    pub(crate) data_bus: u8,
+    pub(crate) keyboard: Keyboard,
+    pub(crate) audio_control: u8,
+    pub(crate) joypads: [Joypad; PORT_COUNT],
+    pub(crate) peripherals: [Peripheral; PORT_COUNT],
+    pub(crate) light_phasers: [LightPhaser; PORT_COUNT],
+    pub(crate) light_sensed: [bool; PORT_COUNT],
+    pub(crate) paddles: [Paddle; PORT_COUNT],
+    pub(crate) sports_pads: [SportsPad; PORT_COUNT],
+    pub(crate) sports_pad_y: [bool; PORT_COUNT],
+    pub(crate) sports_pad_th_cycles: [u64; PORT_COUNT],
+    pub(crate) io_control: u8,
+    pub(crate) reset_button: bool,
+    pub(crate) reset_tapped: bool,
+    pub(crate) pause_button: bool,
+    pub(crate) start_button: bool,
+    pub(crate) nmi_pending: bool,
+    pub(crate) region: Region,
+    pub(crate) glasses_shutter: Option<u8>,
+    pub(crate) frame_eye: Option<Eye>,
+    pub(crate) eye_frames: [Option<Framebuffer>; 2],
+    pub(crate) game_gear_ports: [u8; GAME_GEAR_PORT_COUNT],
+    pub(crate) link: Option<LinkPort>,
+    pub(crate) link_lines: u8,
+    pub(crate) serial_transmit_end: u64,
+    pub(crate) cycles: u64,
+    pub(crate) line_end: u64,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine::new()
+    }
 }
 
 impl Machine {
     pub fn new() -> Machine {
+        Machine::with_vdp_model(VdpModel::Sms2)
+    }
+
+    pub fn with_vdp_model(model: VdpModel) -> Machine {
+        let vdp = Vdp::with_model(model);
+        let clock = vdp.tv_system().cpu_clock();
         Machine {
             cpu: Processor::new(),
             ram: Memory::new(),
+            vdp,
+            psg: Psg::new(),
+            fm: None,
+            audio: StereoBuffer::new(clock, DEFAULT_SAMPLE_RATE),
+            vgm: None,
+            ppi: None,
+            audio_sink: None,
+            unmapped_port_callback: None,
             run: false,
+            system: System::MasterSystem,
+            bios: None,
+            cartridge: None,
+            memory_control: MEMORY_CONTROL_CARTRIDGE,
+            paging: PAGING_RESET,
+            power_on_pattern: PowerOnPattern::Zero,
+            data_bus: 0xFF,
+            keyboard: Keyboard::new(),
+            audio_control: 0,
+            joypads: [Joypad::new(); PORT_COUNT],
+            peripherals: [Peripheral::Joypad; PORT_COUNT],
+            light_phasers: [LightPhaser::new(); PORT_COUNT],
+            light_sensed: [false; PORT_COUNT],
+            paddles: [Paddle::new(); PORT_COUNT],
+            sports_pads: [SportsPad::new(); PORT_COUNT],
+            sports_pad_y: [false; PORT_COUNT],
+            sports_pad_th_cycles: [0; PORT_COUNT],
+            io_control: IO_CONTROL_RESET,
+            reset_button: false,
+            reset_tapped: false,
+            pause_button: false,
+            start_button: false,
+            nmi_pending: false,
+            region: Region::ExportNtsc,
+            game_gear_ports: GAME_GEAR_PORT_DEFAULTS,
+            link: None,
+            link_lines: PARALLEL_IDLE,
+            serial_transmit_end: 0,
+            glasses_shutter: None,
+            frame_eye: None,
+            eye_frames: [None, None],
+            cycles: 0,
+            line_end: 0,
         }
     }
 
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn tv_system(&self) -> TvSystem {
+        self.vdp.tv_system()
+    }
+
+    pub fn set_tv_system(&mut self, tv_system: TvSystem) {
+        self.vdp.set_tv_system(tv_system);
+        self.audio.set_clock_rate(tv_system.cpu_clock());
+        if let Some(fm) = self.fm.as_mut() {
+            fm.set_clock(tv_system.cpu_clock());
+        }
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+        self.set_tv_system(region.tv_system());
+    }
+
+    pub fn framebuffer(&self, viewport: Viewport) -> Framebuffer {
+        self.vdp.screen(viewport)
+    }
+
+    pub fn has_fm_unit(&self) -> bool {
+        self.fm.is_some()
+    }
+
+    pub fn set_fm_unit(&mut self, enabled: bool) {
+        self.fm = if enabled {
+            Some(Ym2413::with_clock(self.tv_system().cpu_clock()))
+        } else {
+            None
+        };
+        self.write_audio_control(0);
+    }
+
+    pub fn audio_control(&self) -> u8 {
+        self.audio_control
+    }
+
+    // Bit 0 enables the FM unit and the PSG plays when both bits agree: 0 selects the PSG,
+    // 1 the FM unit, 2 neither and 3 both.
+    pub fn write_audio_control(&mut self, value: u8) {
+        self.audio_control = value & 0x03;
+        let fm_enabled = value & 0x01 != 0;
+        let psg_enabled = (value & 0x01) == (value >> 1) & 0x01;
+        self.psg
+            .set_muted(self.cycles, !psg_enabled, &mut self.audio);
+        if let Some(fm) = self.fm.as_mut() {
+            fm.set_muted(self.cycles, !fm_enabled, &mut self.audio);
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.audio.sample_rate()
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.audio.set_sample_rate(sample_rate);
+    }
+
+    pub fn set_audio_sink(&mut self, sink: Box<dyn AudioSink>) {
+        self.set_sample_rate(sink.sample_rate());
+        self.audio_sink = Some(sink);
+    }
+
+    pub fn take_audio_sink(&mut self) -> Option<Box<dyn AudioSink>> {
+        self.audio_sink.take()
+    }
+
     pub fn load_at(&mut self, program: &Program, start_address: u16) -> bool {
         let end = start_address as u32 + program.raw().len() as u32;
         let will_fit = end <= 65536;
-        let mut address = start_address;
         if will_fit {
-            for value in program.raw() {
-                self.ram.write_u8(address, *value);
-                address = address.wrapping_add(1);
-            }
+            self.ram.load(start_address, program.raw());
         }
         will_fit
     }
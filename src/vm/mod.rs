@@ -1,10 +1,24 @@
 use crate::vm::cpu::state::State;
 
+pub mod audio;
+pub mod builder;
+mod bus;
 pub mod callbacks;
 pub mod cpu;
+pub mod glasses;
 pub mod instructions;
+mod interrupts;
+pub mod io;
 pub mod machine;
+pub mod paging;
+pub mod power;
+pub mod psg;
 pub mod ram;
+pub mod scheduler;
+pub mod system;
+pub mod vdp;
+pub mod vgm;
+pub mod ym2413;
 
 pub type Register = u8;
 pub type DoubleRegister = (u8, u8);
@@ -0,0 +1,64 @@
+use crate::vm::machine::Machine;
+use crate::vm::system::System;
+
+pub const PAGING_REGISTER_COUNT: usize = 4;
+// Values of 0xFFFC-0xFFFF after a reset: banks 0, 1 and 2 in slots 0, 1 and 2.
+pub const PAGING_RESET: [u8; PAGING_REGISTER_COUNT] = [0x00, 0x00, 0x01, 0x02];
+
+const PAGING_REGISTERS: u16 = 0xFFFC;
+const BANK_SIZE: usize = 0x4000;
+const SLOT_COUNT: usize = 3;
+const PAGED_WINDOW: usize = BANK_SIZE * SLOT_COUNT;
+// The first kilobyte always shows bank 0 so the interrupt vectors survive paging.
+const FIXED_SIZE: usize = 0x0400;
+const MAX_PAGED_SIZE: usize = BANK_SIZE * 0x100;
+
+impl Machine {
+    // The Sega mapper's registers: 0xFFFC controls on-cartridge RAM, which is not emulated,
+    // and 0xFFFD-0xFFFF pick the banks shown in the three 16 KiB slots.
+    pub fn paging_registers(&self) -> [u8; PAGING_REGISTER_COUNT] {
+        self.paging
+    }
+
+    // Images too big for the 48 KiB ROM area go through the mapper on consoles that have the
+    // Sega cartridge slot.
+    pub(crate) fn is_paged(&self, size: usize) -> bool {
+        let slot = self.system == System::MasterSystem || self.system == System::MasterGear;
+        slot && size > PAGED_WINDOW
+            && size <= MAX_PAGED_SIZE
+            && self.ram.map().rom_size() >= PAGED_WINDOW
+    }
+
+    // The mapper listens for writes to 0xFFFC-0xFFFF, which still reach RAM.
+    pub(crate) fn observe_paging_write(&mut self, address: u16, value: u8) {
+        if address < PAGING_REGISTERS {
+            return;
+        }
+        let register = (address - PAGING_REGISTERS) as usize;
+        let changed = self.paging[register] != value;
+        self.paging[register] = value;
+        let paged = self
+            .mapped_image()
+            .is_some_and(|image| self.is_paged(image.len()));
+        if changed && paged {
+            self.map_slots();
+        }
+    }
+
+    // Assembles the ROM area from the banks selected in the paging registers. Banks past the
+    // end of the image wrap around it.
+    pub(crate) fn paged_rom(&self, image: &[u8]) -> Vec<u8> {
+        let banks = image.len().div_ceil(BANK_SIZE);
+        let mask = banks.next_power_of_two() - 1;
+        let mut rom = vec![0xFF; PAGED_WINDOW];
+        for slot in 0..SLOT_COUNT {
+            let start = (self.paging[slot + 1] as usize & mask) * BANK_SIZE;
+            if start < image.len() {
+                let bank = &image[start..image.len().min(start + BANK_SIZE)];
+                rom[slot * BANK_SIZE..slot * BANK_SIZE + bank.len()].copy_from_slice(bank);
+            }
+        }
+        rom[..FIXED_SIZE].copy_from_slice(&image[..FIXED_SIZE]);
+        rom
+    }
+}
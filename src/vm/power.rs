@@ -0,0 +1,120 @@
+use crate::vm::io::game_gear::GAME_GEAR_PORT_DEFAULTS;
+use crate::vm::io::io_control::IO_CONTROL_RESET;
+use crate::vm::io::memory_control::{MEMORY_CONTROL_BIOS, MEMORY_CONTROL_CARTRIDGE};
+use crate::vm::io::peripheral::PORT_COUNT;
+use crate::vm::io::ppi::Ppi;
+use crate::vm::machine::Machine;
+use crate::vm::paging::PAGING_RESET;
+use crate::vm::system::System;
+use crate::vm::vdp::VdpModel;
+
+const MEMORY_SIZE: usize = 0x10000;
+const REGISTER_BYTES: usize = 18;
+
+// What RAM and the CPU registers hold when the console is switched on.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum PowerOnPattern {
+    #[default]
+    Zero,
+    Ones,
+    Random(u64),
+}
+
+impl PowerOnPattern {
+    pub fn fill(self, data: &mut [u8]) {
+        match self {
+            PowerOnPattern::Zero => data.iter_mut().for_each(|value| *value = 0x00),
+            PowerOnPattern::Ones => data.iter_mut().for_each(|value| *value = 0xFF),
+            PowerOnPattern::Random(seed) => {
+                // xorshift64*, which never leaves the zero state, so zero seeds are remapped.
+                let mut state = if seed == 0 {
+                    0x9E37_79B9_7F4A_7C15
+                } else {
+                    seed
+                };
+                for value in data.iter_mut() {
+                    state ^= state >> 12;
+                    state ^= state << 25;
+                    state ^= state >> 27;
+                    *value = (state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8;
+                }
+            }
+        }
+    }
+}
+
+impl Machine {
+    pub fn power_on_pattern(&self) -> PowerOnPattern {
+        self.power_on_pattern
+    }
+
+    pub fn set_power_on_pattern(&mut self, pattern: PowerOnPattern) {
+        self.power_on_pattern = pattern;
+    }
+
+    // Presses the console's reset button for a frame. The Mark III and the original Master
+    // System only report it in port 0xDD for the game to act on, the SC-3000's RESET key
+    // raises an NMI and a bare Z80 board pulls the reset line. The Master System II, the
+    // SG-1000 and the Game Gear have no reset button.
+    pub fn reset(&mut self) {
+        if self.vdp.model() == VdpModel::GameGear {
+            return;
+        }
+        match self.system {
+            System::MasterSystem if self.vdp.model() == VdpModel::Sms1 => {
+                self.reset_button = true;
+                self.reset_tapped = true;
+            }
+            System::Sc3000 => self.nmi_pending = true,
+            System::Generic => self.hard_reset(),
+            System::MasterSystem | System::Sg1000 | System::MasterGear => {}
+        }
+    }
+
+    // Lets go of a button pressed by `reset` once the frame it was pressed in has run.
+    pub(crate) fn release_reset_tap(&mut self) {
+        if self.reset_tapped {
+            self.reset_tapped = false;
+            self.reset_button = false;
+        }
+    }
+
+    // Pulls the reset line: the chips and mapper go back to their power-on state but RAM,
+    // VRAM and the CPU's general registers are left alone.
+    pub fn hard_reset(&mut self) {
+        self.cpu.reset();
+        self.vdp.reset();
+        self.psg.reset(self.cycles, &mut self.audio);
+        if let Some(fm) = self.fm.as_mut() {
+            fm.reset(self.cycles, &mut self.audio);
+        }
+        self.write_audio_control(0);
+        if let Some(ppi) = self.ppi.as_mut() {
+            *ppi = Ppi::new();
+        }
+        self.io_control = IO_CONTROL_RESET;
+        self.sports_pad_y = [false; PORT_COUNT];
+        self.game_gear_ports = GAME_GEAR_PORT_DEFAULTS;
+        self.nmi_pending = false;
+        self.memory_control = if self.bios.is_some() {
+            MEMORY_CONTROL_BIOS
+        } else {
+            MEMORY_CONTROL_CARTRIDGE
+        };
+        self.paging = PAGING_RESET;
+        self.map_slots();
+    }
+
+    // Switches the console off and on: RAM and registers are refilled from the power-on
+    // pattern before the reset, and the BIOS or cartridge is mapped in again.
+    pub fn power_cycle(&mut self) {
+        let mut contents = vec![0; MEMORY_SIZE + REGISTER_BYTES];
+        self.power_on_pattern.fill(&mut contents);
+        self.ram.power_on(&contents[..MEMORY_SIZE]);
+        self.cpu.power_on(&contents[MEMORY_SIZE..]);
+        self.glasses_shutter = None;
+        self.frame_eye = None;
+        self.eye_frames = [None, None];
+        self.hard_reset();
+    }
+}
@@ -0,0 +1,251 @@
+use crate::vm::audio::stereo_buffer::StereoBuffer;
+
+pub const CHANNEL_COUNT: usize = 4;
+pub const NOISE_CHANNEL: usize = 3;
+
+const CLOCK_DIVIDER: u64 = 16;
+// The Sega variant has a 16-bit noise shift register tapped at bits 0 and 3.
+pub const SEGA_LFSR_TAPS: u16 = 0x0009;
+pub const SEGA_LFSR_WIDTH: u8 = 16;
+const MAX_AMPLITUDE: f64 = 8191.0;
+
+pub struct Psg {
+    periods: [u16; CHANNEL_COUNT],
+    attenuations: [u8; CHANNEL_COUNT],
+    counters: [u16; CHANNEL_COUNT],
+    outputs: [bool; CHANNEL_COUNT],
+    volumes: [i16; 16],
+    lfsr: u16,
+    lfsr_taps: u16,
+    lfsr_width: u8,
+    latched_channel: usize,
+    latched_volume: bool,
+    next_tick: u64,
+    stereo: u8,
+    amplitude: (i32, i32),
+    muted: bool,
+    mute_mask: u8,
+}
+
+impl Default for Psg {
+    fn default() -> Self {
+        Psg::new()
+    }
+}
+
+impl Psg {
+    pub fn new() -> Psg {
+        let mut volumes = [0; 16];
+        for (attenuation, volume) in volumes.iter_mut().enumerate().take(15) {
+            let decibels = attenuation as f64 * -2.0;
+            *volume = (MAX_AMPLITUDE * 10f64.powf(decibels / 20.0)) as i16;
+        }
+        Psg {
+            periods: [0; CHANNEL_COUNT],
+            attenuations: [0x0F; CHANNEL_COUNT],
+            counters: [0; CHANNEL_COUNT],
+            outputs: [true; CHANNEL_COUNT],
+            volumes,
+            lfsr: 1 << (SEGA_LFSR_WIDTH - 1),
+            lfsr_taps: SEGA_LFSR_TAPS,
+            lfsr_width: SEGA_LFSR_WIDTH,
+            latched_channel: 0,
+            latched_volume: false,
+            next_tick: 0,
+            stereo: 0xFF,
+            amplitude: (0, 0),
+            muted: false,
+            mute_mask: 0,
+        }
+    }
+
+    // Restores the power-on registers while keeping the timing and mute settings.
+    pub fn reset(&mut self, cycle: u64, output: &mut StereoBuffer) {
+        let (taps, width) = (self.lfsr_taps, self.lfsr_width);
+        *self = Psg {
+            next_tick: self.next_tick,
+            amplitude: self.amplitude,
+            muted: self.muted,
+            mute_mask: self.mute_mask,
+            ..Psg::new()
+        };
+        self.set_noise_shape(taps, width);
+        self.update_output(cycle, output);
+    }
+
+    pub fn tone_period(&self, channel: usize) -> u16 {
+        self.periods[channel]
+    }
+
+    pub fn attenuation(&self, channel: usize) -> u8 {
+        self.attenuations[channel]
+    }
+
+    pub fn noise_control(&self) -> u8 {
+        self.periods[NOISE_CHANNEL] as u8
+    }
+
+    pub fn noise_taps(&self) -> u16 {
+        self.lfsr_taps
+    }
+
+    pub fn noise_width(&self) -> u8 {
+        self.lfsr_width
+    }
+
+    // Other SN76489 variants differ in the width and taps of the noise shift register, which
+    // gives their white noise a different pattern. `width` must be between 1 and 16.
+    pub fn set_noise_shape(&mut self, taps: u16, width: u8) {
+        self.lfsr_taps = taps;
+        self.lfsr_width = width;
+        self.lfsr = self.lfsr_reset();
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, cycle: u64, muted: bool, output: &mut StereoBuffer) {
+        self.run_until(cycle, output);
+        self.muted = muted;
+        self.update_output(cycle, output);
+    }
+
+    pub fn mute_mask(&self) -> u8 {
+        self.mute_mask
+    }
+
+    pub fn set_mute_mask(&mut self, mask: u8) {
+        self.mute_mask = mask;
+    }
+
+    pub fn stereo(&self) -> u8 {
+        self.stereo
+    }
+
+    pub fn write_stereo(&mut self, cycle: u64, value: u8, output: &mut StereoBuffer) {
+        self.run_until(cycle, output);
+        self.stereo = value;
+        self.update_output(cycle, output);
+    }
+
+    pub fn write(&mut self, cycle: u64, value: u8, output: &mut StereoBuffer) {
+        self.run_until(cycle, output);
+        if value & 0x80 != 0 {
+            self.latched_channel = ((value >> 5) & 0x03) as usize;
+            self.latched_volume = value & 0x10 != 0;
+            self.write_register(value as u16 & 0x0F, 0x0F);
+        } else if !self.latched_volume && self.latched_channel != NOISE_CHANNEL {
+            self.write_register((value as u16 & 0x3F) << 4, 0x3F0);
+        } else {
+            self.write_register(value as u16 & 0x0F, 0x0F);
+        }
+        self.update_output(cycle, output);
+    }
+
+    pub fn run_until(&mut self, cycle: u64, output: &mut StereoBuffer) {
+        while self.next_tick <= cycle {
+            self.tick();
+            self.update_output(self.next_tick, output);
+            self.next_tick += CLOCK_DIVIDER;
+        }
+    }
+
+    fn update_output(&mut self, cycle: u64, output: &mut StereoBuffer) {
+        let amplitude = if self.muted { (0, 0) } else { self.mix() };
+        output.add_delta(
+            cycle,
+            amplitude.0 - self.amplitude.0,
+            amplitude.1 - self.amplitude.1,
+        );
+        self.amplitude = amplitude;
+    }
+
+    fn write_register(&mut self, value: u16, mask: u16) {
+        let channel = self.latched_channel;
+        if self.latched_volume {
+            self.attenuations[channel] = value as u8;
+        } else if channel == NOISE_CHANNEL {
+            self.periods[channel] = value & 0x07;
+            self.lfsr = self.lfsr_reset();
+        } else {
+            self.periods[channel] = (self.periods[channel] & !mask) | value;
+        }
+    }
+
+    fn tick(&mut self) {
+        for channel in 0..NOISE_CHANNEL {
+            if self.counters[channel] > 0 {
+                self.counters[channel] -= 1;
+            }
+            if self.counters[channel] == 0 {
+                let period = self.periods[channel];
+                self.counters[channel] = period;
+                self.outputs[channel] = period <= 1 || !self.outputs[channel];
+            }
+        }
+
+        if self.counters[NOISE_CHANNEL] > 0 {
+            self.counters[NOISE_CHANNEL] -= 1;
+        }
+        if self.counters[NOISE_CHANNEL] == 0 {
+            self.counters[NOISE_CHANNEL] = match self.periods[NOISE_CHANNEL] & 0x03 {
+                0 => 0x10,
+                1 => 0x20,
+                2 => 0x40,
+                _ => self.periods[2].max(1),
+            };
+            self.outputs[NOISE_CHANNEL] = !self.outputs[NOISE_CHANNEL];
+            if self.outputs[NOISE_CHANNEL] {
+                self.shift_lfsr();
+            }
+        }
+    }
+
+    fn lfsr_reset(&self) -> u16 {
+        1 << (self.lfsr_width - 1)
+    }
+
+    fn shift_lfsr(&mut self) {
+        let feedback = if self.periods[NOISE_CHANNEL] & 0x04 != 0 {
+            (self.lfsr & self.lfsr_taps).count_ones() as u16 & 1
+        } else {
+            self.lfsr & 1
+        };
+        self.lfsr = (self.lfsr >> 1) | (feedback << (self.lfsr_width - 1));
+    }
+
+    fn channel_output(&self, channel: usize) -> i16 {
+        let volume = self.volumes[self.attenuations[channel] as usize];
+        let high = if channel == NOISE_CHANNEL {
+            self.lfsr & 1 != 0
+        } else {
+            self.outputs[channel]
+        };
+        if high {
+            volume
+        } else {
+            -volume
+        }
+    }
+
+    // The upper nibble of the stereo register routes channels to the left output and the
+    // lower nibble to the right, with channel 0 in the lowest bit of each.
+    fn mix(&self) -> (i32, i32) {
+        let mut left = 0;
+        let mut right = 0;
+        for channel in 0..CHANNEL_COUNT {
+            if self.mute_mask & (1 << channel) != 0 {
+                continue;
+            }
+            let output = self.channel_output(channel) as i32;
+            if self.stereo & (0x10 << channel) != 0 {
+                left += output;
+            }
+            if self.stereo & (0x01 << channel) != 0 {
+                right += output;
+            }
+        }
+        (left, right)
+    }
+}
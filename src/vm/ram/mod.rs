@@ -1,31 +1,155 @@
 use crate::vm::cpu::alu;
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MemoryMap {
+    Flat,
+    Cartridge { rom_end: u16, ram_mask: u16 },
+}
+
+impl MemoryMap {
+    pub const SG1000: MemoryMap = MemoryMap::Cartridge {
+        rom_end: 0xC000,
+        ram_mask: 0x03FF,
+    };
+    pub const SC3000: MemoryMap = MemoryMap::Cartridge {
+        rom_end: 0xC000,
+        ram_mask: 0x07FF,
+    };
+    // BASIC Level III cartridges add 32 KiB of RAM from 0x8000.
+    pub const SC3000_BASIC: MemoryMap = MemoryMap::Cartridge {
+        rom_end: 0x8000,
+        ram_mask: 0x7FFF,
+    };
+
+    // RAM is mirrored down to 16 KiB below the top of memory; anything larger pushes the
+    // ROM area down instead.
+    pub fn with_ram_size(size: usize) -> MemoryMap {
+        if size >= 0x10000 {
+            return MemoryMap::Flat;
+        }
+        let size = size.max(1).next_power_of_two();
+        MemoryMap::Cartridge {
+            rom_end: (0x10000 - size.max(0x4000)) as u16,
+            ram_mask: (size - 1) as u16,
+        }
+    }
+
+    pub fn rom_size(self) -> usize {
+        match self {
+            MemoryMap::Flat => 0x10000,
+            MemoryMap::Cartridge { rom_end, .. } => rom_end as usize,
+        }
+    }
+}
+
 pub struct Memory {
     data: [u8; 65536],
+    map: MemoryMap,
+    rom_mapped: bool,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Memory::new()
+    }
 }
 
 impl Memory {
     pub fn new() -> Memory {
-        Memory { data: [0; 65536] }
+        Memory::with_map(MemoryMap::Flat)
+    }
+
+    pub fn with_map(map: MemoryMap) -> Memory {
+        Memory {
+            data: [0; 65536],
+            map,
+            rom_mapped: true,
+        }
+    }
+
+    pub fn map(&self) -> MemoryMap {
+        self.map
     }
 
+    pub fn set_map(&mut self, map: MemoryMap) {
+        self.map = map;
+    }
+
+    // Only RAM takes the power-on contents; the ROM area floats high until an image is
+    // mapped in.
+    pub fn power_on(&mut self, contents: &[u8]) {
+        for (address, value) in contents.iter().enumerate() {
+            self.data[address] = if self.is_rom(address as u16) {
+                0xFF
+            } else {
+                *value
+            };
+        }
+    }
+
+    pub fn rom_mapped(&self) -> bool {
+        self.rom_mapped
+    }
+
+    // With no ROM selected the data bus floats and the pull-up resistors read as 0xFF.
+    pub fn set_rom_mapped(&mut self, mapped: bool) {
+        self.rom_mapped = mapped;
+    }
+
+    pub fn is_mapped(&self, address: u16) -> bool {
+        self.rom_mapped || !self.is_rom(address)
+    }
+
+    // Reads without a bus around them see the pull-ups on unmapped addresses; the CPU goes
+    // through `Machine::read_memory` instead.
     pub fn read_u8(&self, address: u16) -> u8 {
-        self.data[address as usize]
+        if !self.is_mapped(address) {
+            return 0xFF;
+        }
+        self.data[self.location(address)]
     }
 
     pub fn read_u16(&self, address: u16) -> u16 {
         let low = self.read_u8(address) as u16;
-        let high = self.read_u8(address + 1) as u16;
+        let high = self.read_u8(address.wrapping_add(1)) as u16;
         (high << 8) | low
     }
 
     pub fn write_u8(&mut self, address: u16, value: u8) {
-        self.data[address as usize] = value;
+        if !self.is_rom(address) {
+            self.data[self.location(address)] = value;
+        }
+    }
+
+    // Loads data through the memory map, including into cartridge ROM.
+    pub fn load(&mut self, address: u16, data: &[u8]) {
+        let mut address = address;
+        for value in data {
+            self.data[self.location(address)] = *value;
+            address = address.wrapping_add(1);
+        }
     }
 
     pub fn write_u16(&mut self, address: u16, value: u16) {
-        let (low, high) = alu::get_octets(value);
+        let (high, low) = alu::get_octets(value);
         self.write_u8(address, low);
-        self.write_u8(address + 1, high);
+        self.write_u8(address.wrapping_add(1), high);
+    }
+
+    fn is_rom(&self, address: u16) -> bool {
+        match self.map {
+            MemoryMap::Flat => false,
+            MemoryMap::Cartridge { rom_end, .. } => address < rom_end,
+        }
+    }
+
+    // Cartridge systems mirror their small RAM across everything above the ROM.
+    fn location(&self, address: u16) -> usize {
+        match self.map {
+            MemoryMap::Cartridge { rom_end, ram_mask } if address >= rom_end => {
+                (rom_end + ((address - rom_end) & ram_mask)) as usize
+            }
+            _ => address as usize,
+        }
     }
 }
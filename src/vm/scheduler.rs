@@ -0,0 +1,59 @@
+use crate::vm::callbacks::Callbacks;
+use crate::vm::glasses::Eye;
+use crate::vm::machine::Machine;
+use crate::vm::vdp::framebuffer::Framebuffer;
+use crate::vm::vdp::Viewport;
+
+pub const CYCLES_PER_LINE: u64 = 228;
+
+pub struct Frame {
+    pub video: Framebuffer,
+    pub audio: Vec<i16>,
+    pub eye: Option<Eye>,
+}
+
+impl Machine {
+    pub fn run_frame(&mut self) -> Frame {
+        self.run_frame_with(&mut Callbacks::new())
+    }
+
+    pub fn run_frame_with(&mut self, callbacks: &mut Callbacks) -> Frame {
+        let lines = self.vdp.tv_system().lines_per_frame();
+        if self.cycles > self.line_end + CYCLES_PER_LINE {
+            self.line_end = self.cycles;
+        }
+        self.latch_glasses_eye();
+        for line in 0..lines {
+            self.vdp.begin_line(line);
+            self.poll_link();
+            self.line_end += CYCLES_PER_LINE;
+            self.synchronize_vdp();
+            self.sense_light_phasers();
+            while self.cycles < self.line_end {
+                self.service_interrupts();
+                if self.cpu.is_halted() {
+                    self.nop();
+                } else {
+                    self.execute_with(callbacks);
+                }
+            }
+            self.synchronize_vdp();
+            self.sense_light_phasers();
+            self.vdp.end_line();
+            self.psg.run_until(self.cycles, &mut self.audio);
+            if let Some(fm) = self.fm.as_mut() {
+                fm.run_until(self.cycles, &mut self.audio);
+            }
+        }
+        self.release_reset_tap();
+        self.audio.end_frame(self.cycles);
+        let audio = self.audio.take_samples();
+        if let Some(sink) = self.audio_sink.as_mut() {
+            sink.write_samples(&audio);
+        }
+        let video = self.vdp.screen(Viewport::Visible);
+        let eye = self.frame_eye;
+        self.capture_eye_frame(eye, &video);
+        Frame { video, audio, eye }
+    }
+}
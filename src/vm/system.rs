@@ -0,0 +1,80 @@
+use crate::vm::io::memory_control::MEMORY_CONTROL_BIOS;
+use crate::vm::io::ppi::Ppi;
+use crate::vm::machine::Machine;
+use crate::vm::ram::MemoryMap;
+use crate::vm::vdp::VdpModel;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum System {
+    MasterSystem,
+    Sg1000,
+    Sc3000,
+    MasterGear,
+    Generic,
+}
+
+impl System {
+    pub fn vdp_model(self) -> VdpModel {
+        match self {
+            System::MasterSystem | System::Generic => VdpModel::Sms2,
+            System::MasterGear => VdpModel::GameGear,
+            System::Sg1000 | System::Sc3000 => VdpModel::Tms9918a,
+        }
+    }
+
+    pub fn memory_map(self) -> MemoryMap {
+        match self {
+            System::MasterSystem | System::MasterGear | System::Generic => MemoryMap::Flat,
+            System::Sg1000 => MemoryMap::SG1000,
+            System::Sc3000 => MemoryMap::SC3000,
+        }
+    }
+
+    pub fn has_io(self) -> bool {
+        self != System::Generic
+    }
+
+    pub fn has_sg1000_io(self) -> bool {
+        self == System::Sg1000 || self == System::Sc3000
+    }
+
+    pub fn has_keyboard(self) -> bool {
+        self == System::Sc3000
+    }
+}
+
+impl Machine {
+    pub fn with_system(system: System) -> Machine {
+        let mut machine = Machine::with_vdp_model(system.vdp_model());
+        machine.system = system;
+        machine.ram.set_map(system.memory_map());
+        machine.vdp.set_sms_mode(system == System::MasterGear);
+        if system.has_keyboard() {
+            machine.ppi = Some(Ppi::new());
+        }
+        machine
+    }
+
+    pub fn system(&self) -> System {
+        self.system
+    }
+
+    pub fn load_cartridge(&mut self, rom: &[u8]) -> bool {
+        let will_fit = rom.len() <= self.ram.map().rom_size() || self.is_paged(rom.len());
+        if will_fit {
+            self.cartridge = Some(rom.to_vec());
+            self.map_slots();
+        }
+        will_fit
+    }
+
+    pub fn load_bios(&mut self, bios: &[u8]) -> bool {
+        let will_fit = bios.len() <= self.ram.map().rom_size() || self.is_paged(bios.len());
+        if will_fit {
+            self.bios = Some(bios.to_vec());
+            self.memory_control = MEMORY_CONTROL_BIOS;
+            self.map_slots();
+        }
+        will_fit
+    }
+}
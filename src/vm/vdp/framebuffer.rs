@@ -0,0 +1,99 @@
+#[derive(Clone)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u32 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Framebuffer {
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in y..y + height {
+            let start = row * self.width + x;
+            pixels.extend_from_slice(&self.pixels[start..start + width]);
+        }
+        Framebuffer {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // The Game Gear's LCD controller fits a Master System picture on its screen by blending
+    // fixed groups: every eight pixels of a line become five and every four lines become three.
+    pub fn game_gear_downscale(&self) -> Framebuffer {
+        let width = self.width / COLUMN_GROUP * COLUMN_BLEND.len();
+        let height = self.height / LINE_GROUP * LINE_BLEND.len();
+        let total = (COLUMN_GROUP * LINE_GROUP) as u32;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let first_line = y / LINE_BLEND.len() * LINE_GROUP;
+            let lines = LINE_BLEND[y % LINE_BLEND.len()];
+            for x in 0..width {
+                let first_column = x / COLUMN_BLEND.len() * COLUMN_GROUP;
+                let columns = COLUMN_BLEND[x % COLUMN_BLEND.len()];
+                let mut sums = [0; 3];
+                for (line, line_weight) in lines.iter() {
+                    for (column, column_weight) in columns.iter() {
+                        let pixel = self.pixel(first_column + column, first_line + line);
+                        let weight = line_weight * column_weight;
+                        for (channel, sum) in sums.iter_mut().enumerate() {
+                            *sum += ((pixel >> (16 - channel * 8)) & 0xFF) * weight;
+                        }
+                    }
+                }
+                pixels.push(
+                    sums.iter()
+                        .fold(0, |colour, sum| (colour << 8) | ((sum + total / 2) / total)),
+                );
+            }
+        }
+        Framebuffer {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub(crate) fn line_mut(&mut self, y: usize) -> &mut [u32] {
+        let start = y * self.width;
+        &mut self.pixels[start..start + self.width]
+    }
+}
+
+// Each output pixel of a group lists the source pixels it blends with their weights, which add
+// up to the length of the source group.
+const COLUMN_GROUP: usize = 8;
+const COLUMN_BLEND: [&[(usize, u32)]; 5] = [
+    &[(0, 5), (1, 3)],
+    &[(1, 2), (2, 5), (3, 1)],
+    &[(3, 4), (4, 4)],
+    &[(4, 1), (5, 5), (6, 2)],
+    &[(6, 3), (7, 5)],
+];
+const LINE_GROUP: usize = 4;
+const LINE_BLEND: [&[(usize, u32)]; 3] = [&[(0, 3), (1, 1)], &[(1, 2), (2, 2)], &[(2, 1), (3, 3)]];
@@ -0,0 +1,453 @@
+pub mod framebuffer;
+mod mode4;
+mod timing;
+mod tms9918;
+
+use std::collections::VecDeque;
+
+use crate::vm::vdp::framebuffer::Framebuffer;
+
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 192;
+pub const MAX_SCREEN_HEIGHT: usize = 240;
+pub const GAME_GEAR_WIDTH: usize = 160;
+pub const GAME_GEAR_HEIGHT: usize = 144;
+
+const VRAM_SIZE: usize = 0x4000;
+const CRAM_SIZE: usize = 32;
+const GAME_GEAR_CRAM_SIZE: usize = 64;
+const REGISTER_COUNT: usize = 11;
+
+const STATUS_FRAME_INTERRUPT: u8 = 0x80;
+const STATUS_SPRITE_OVERFLOW: u8 = 0x40;
+const STATUS_SPRITE_COLLISION: u8 = 0x20;
+
+const CODE_VRAM_READ: u8 = 0;
+const CODE_REGISTER_WRITE: u8 = 2;
+const CODE_CRAM_WRITE: u8 = 3;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DisplayMode {
+    Graphics1,
+    Graphics2,
+    Text,
+    Multicolor,
+    Mode4,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum VdpModel {
+    Sms1,
+    Sms2,
+    GameGear,
+    MegaDrive,
+    Tms9918a,
+}
+
+impl VdpModel {
+    pub fn part_number(self) -> &'static str {
+        match self {
+            VdpModel::Sms1 => "315-5124",
+            VdpModel::Sms2 => "315-5246",
+            VdpModel::GameGear => "315-5378",
+            VdpModel::MegaDrive => "315-5313",
+            VdpModel::Tms9918a => "TMS9918A",
+        }
+    }
+
+    pub fn masks_name_table(self) -> bool {
+        self == VdpModel::Sms1
+    }
+
+    pub fn has_sprite_zoom_bug(self) -> bool {
+        self == VdpModel::Sms1
+    }
+
+    pub fn supports_extended_heights(self) -> bool {
+        self == VdpModel::Sms2 || self == VdpModel::GameGear
+    }
+
+    pub fn supports_mode4(self) -> bool {
+        self != VdpModel::Tms9918a
+    }
+
+    pub fn supports_tms_modes(self) -> bool {
+        self != VdpModel::MegaDrive
+    }
+
+    fn cram_size(self) -> usize {
+        match self {
+            VdpModel::GameGear => GAME_GEAR_CRAM_SIZE,
+            _ => CRAM_SIZE,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Viewport {
+    Visible,
+    Full,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TvSystem {
+    Ntsc,
+    Pal,
+}
+
+impl TvSystem {
+    pub fn lines_per_frame(self) -> u16 {
+        match self {
+            TvSystem::Ntsc => 262,
+            TvSystem::Pal => 313,
+        }
+    }
+
+    pub fn cpu_clock(self) -> u32 {
+        match self {
+            TvSystem::Ntsc => 3_579_545,
+            TvSystem::Pal => 3_546_893,
+        }
+    }
+
+    fn v_counter(self, line: u16, height: usize) -> u8 {
+        let jump = match (self, height) {
+            (TvSystem::Ntsc, 192) => (0xDA, 6),
+            (TvSystem::Ntsc, 224) => (0xEA, 6),
+            (TvSystem::Ntsc, _) => (0xFFFF, 0),
+            (TvSystem::Pal, 192) => (0xF2, 57),
+            (TvSystem::Pal, 224) => (0x102, 57),
+            (TvSystem::Pal, _) => (0x10A, 57),
+        };
+        if line > jump.0 {
+            (line - jump.1) as u8
+        } else {
+            line as u8
+        }
+    }
+}
+
+pub struct Vdp {
+    vram: Vec<u8>,
+    cram: Vec<u8>,
+    cram_latch: u8,
+    registers: [u8; REGISTER_COUNT],
+    address: u16,
+    code: u8,
+    latch: Option<u8>,
+    read_buffer: u8,
+    status: u8,
+    line_counter: u8,
+    line_interrupt: bool,
+    line: u16,
+    h_counter: u8,
+    tv_system: TvSystem,
+    model: VdpModel,
+    sms_mode: bool,
+    framebuffer: Framebuffer,
+    drawn_x: usize,
+    accurate_timing: bool,
+    cycle: u64,
+    line_cycle: u64,
+    next_access_slot: u64,
+    pending_writes: VecDeque<(u64, usize, u8)>,
+    delayed_writes: u64,
+    sprite_line: [u8; SCREEN_WIDTH],
+}
+
+impl Default for Vdp {
+    fn default() -> Self {
+        Vdp::new()
+    }
+}
+
+impl Vdp {
+    pub fn new() -> Vdp {
+        Vdp::with_model(VdpModel::Sms2)
+    }
+
+    pub fn with_model(model: VdpModel) -> Vdp {
+        Vdp {
+            vram: vec![0; VRAM_SIZE],
+            cram: vec![0; model.cram_size()],
+            cram_latch: 0,
+            registers: [0; REGISTER_COUNT],
+            address: 0,
+            code: 0,
+            latch: None,
+            read_buffer: 0,
+            status: 0,
+            line_counter: 0xFF,
+            line_interrupt: false,
+            line: 0,
+            h_counter: 0,
+            tv_system: TvSystem::Ntsc,
+            model,
+            sms_mode: false,
+            framebuffer: Framebuffer::new(SCREEN_WIDTH, MAX_SCREEN_HEIGHT),
+            drawn_x: 0,
+            accurate_timing: false,
+            cycle: 0,
+            line_cycle: 0,
+            next_access_slot: 0,
+            pending_writes: VecDeque::new(),
+            delayed_writes: 0,
+            sprite_line: [0; SCREEN_WIDTH],
+        }
+    }
+
+    // Clears the registers and access state; VRAM and CRAM keep their contents.
+    pub fn reset(&mut self) {
+        self.flush_pending_writes();
+        let mut vdp = Vdp::with_model(self.model);
+        std::mem::swap(&mut vdp.vram, &mut self.vram);
+        std::mem::swap(&mut vdp.cram, &mut self.cram);
+        vdp.tv_system = self.tv_system;
+        vdp.sms_mode = self.sms_mode;
+        vdp.accurate_timing = self.accurate_timing;
+        vdp.cycle = self.cycle;
+        vdp.next_access_slot = self.next_access_slot;
+        *self = vdp;
+    }
+
+    pub fn tv_system(&self) -> TvSystem {
+        self.tv_system
+    }
+
+    pub fn set_tv_system(&mut self, tv_system: TvSystem) {
+        self.tv_system = tv_system;
+    }
+
+    pub fn model(&self) -> VdpModel {
+        self.model
+    }
+
+    pub fn sms_mode(&self) -> bool {
+        self.sms_mode
+    }
+
+    // The Game Gear VDP in Master System mode uses the 6-bit palette and scales the whole
+    // 256x192 picture to fit the LCD.
+    pub fn set_sms_mode(&mut self, enabled: bool) {
+        self.sms_mode = enabled && self.model == VdpModel::GameGear;
+    }
+
+    pub fn screen(&self, viewport: Viewport) -> Framebuffer {
+        let height = self.active_height();
+        match (self.model, viewport) {
+            (VdpModel::GameGear, Viewport::Visible) if self.sms_mode => self
+                .framebuffer
+                .crop(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT)
+                .game_gear_downscale(),
+            (VdpModel::GameGear, Viewport::Visible) => self.framebuffer.crop(
+                (SCREEN_WIDTH - GAME_GEAR_WIDTH) / 2,
+                (height - GAME_GEAR_HEIGHT) / 2,
+                GAME_GEAR_WIDTH,
+                GAME_GEAR_HEIGHT,
+            ),
+            _ => self.framebuffer.crop(0, 0, SCREEN_WIDTH, height),
+        }
+    }
+
+    pub fn vram(&self) -> &[u8] {
+        &self.vram
+    }
+
+    pub fn cram(&self) -> &[u8] {
+        &self.cram
+    }
+
+    pub fn register(&self, index: usize) -> u8 {
+        self.registers[index]
+    }
+
+    pub fn display_mode(&self) -> DisplayMode {
+        if self.registers[0] & 0x04 != 0 && self.model.supports_mode4() {
+            DisplayMode::Mode4
+        } else if self.registers[0] & 0x02 != 0 {
+            DisplayMode::Graphics2
+        } else if self.registers[1] & 0x10 != 0 {
+            DisplayMode::Text
+        } else if self.registers[1] & 0x08 != 0 {
+            DisplayMode::Multicolor
+        } else {
+            DisplayMode::Graphics1
+        }
+    }
+
+    pub fn active_height(&self) -> usize {
+        let extended = self.display_mode() == DisplayMode::Mode4
+            && self.registers[0] & 0x02 != 0
+            && self.model.supports_extended_heights();
+        match self.registers[1] & 0x18 {
+            0x10 if extended => 224,
+            0x08 if extended => 240,
+            _ => SCREEN_HEIGHT,
+        }
+    }
+
+    pub fn line(&self) -> u16 {
+        self.line
+    }
+
+    pub fn read_data(&mut self) -> u8 {
+        self.latch = None;
+        self.flush_pending_writes();
+        let value = self.read_buffer;
+        self.read_buffer = self.vram[self.vram_address()];
+        self.increment_address();
+        value
+    }
+
+    pub fn write_data(&mut self, value: u8) {
+        self.latch = None;
+        if self.code == CODE_CRAM_WRITE {
+            self.catch_up();
+            self.write_cram(value);
+        } else {
+            let index = self.vram_address();
+            self.write_vram(index, value);
+        }
+        self.read_buffer = value;
+        self.increment_address();
+    }
+
+    pub fn read_control(&mut self) -> u8 {
+        self.latch = None;
+        let value = self.status;
+        self.status &= 0x1F;
+        self.line_interrupt = false;
+        value
+    }
+
+    pub fn write_control(&mut self, value: u8) {
+        match self.latch.take() {
+            None => {
+                self.address = (self.address & 0xFF00) | value as u16;
+                self.latch = Some(value);
+            }
+            Some(low) => {
+                self.code = value >> 6;
+                self.address = ((value as u16 & 0x3F) << 8) | low as u16;
+                match self.code {
+                    CODE_VRAM_READ => {
+                        self.flush_pending_writes();
+                        self.read_buffer = self.vram[self.vram_address()];
+                        self.increment_address();
+                    }
+                    CODE_REGISTER_WRITE => {
+                        let index = (value & 0x0F) as usize;
+                        if index < REGISTER_COUNT {
+                            self.catch_up();
+                            self.registers[index] = low;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    pub fn read_v_counter(&self) -> u8 {
+        self.tv_system.v_counter(self.line, self.active_height())
+    }
+
+    pub fn read_h_counter(&self) -> u8 {
+        self.h_counter
+    }
+
+    pub(crate) fn latch_h_counter(&mut self, value: u8) {
+        self.h_counter = value;
+    }
+
+    pub(crate) fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    pub fn interrupt_pending(&self) -> bool {
+        let frame = self.status & STATUS_FRAME_INTERRUPT != 0 && self.registers[1] & 0x20 != 0;
+        let line =
+            self.line_interrupt && self.registers[0] & 0x10 != 0 && self.model.supports_mode4();
+        frame || line
+    }
+
+    pub(crate) fn begin_line(&mut self, line: u16) {
+        self.line = line;
+        self.drawn_x = 0;
+        if line == self.active_height() as u16 + 1 {
+            self.status |= STATUS_FRAME_INTERRUPT;
+        }
+    }
+
+    pub(crate) fn end_line(&mut self) {
+        let line = self.line as usize;
+        let height = self.active_height();
+        if line < height {
+            self.render_span(SCREEN_WIDTH);
+        }
+        if line <= height {
+            let (counter, underflow) = self.line_counter.overflowing_sub(1);
+            if underflow {
+                self.line_counter = self.registers[10];
+                self.line_interrupt = true;
+            } else {
+                self.line_counter = counter;
+            }
+        } else {
+            self.line_counter = self.registers[10];
+        }
+    }
+
+    fn render_line(&mut self, line: usize) {
+        match self.display_mode() {
+            DisplayMode::Mode4 => self.render_mode4_line(line),
+            _ if self.model.supports_tms_modes() => self.render_tms_line(line),
+            _ => self.render_blank_line(line),
+        }
+    }
+
+    // Sprites are evaluated once per line, so the collision and overflow flags are only set
+    // once however many spans the line is drawn in.
+    fn evaluate_sprites(&mut self, line: usize) {
+        self.sprite_line = [0; SCREEN_WIDTH];
+        if self.registers[1] & 0x40 == 0 {
+            return;
+        }
+        match self.display_mode() {
+            DisplayMode::Mode4 => self.mode4_sprites(line),
+            DisplayMode::Text => {}
+            _ if self.model.supports_tms_modes() => self.tms_sprites(line),
+            _ => {}
+        }
+    }
+
+    // The Mega Drive VDP has no TMS modes and shows only the Mode 4 backdrop colour instead.
+    fn render_blank_line(&mut self, line: usize) {
+        let backdrop = self.mode4_colour(16 + (self.registers[7] & 0x0F) as usize);
+        for pixel in self.framebuffer.line_mut(line) {
+            *pixel = backdrop;
+        }
+    }
+
+    fn write_cram(&mut self, value: u8) {
+        let index = self.address as usize % self.cram.len();
+        if self.model != VdpModel::GameGear {
+            self.cram[index] = value;
+        } else if self.sms_mode {
+            self.cram[index % CRAM_SIZE] = value;
+        } else if index & 1 == 0 {
+            self.cram_latch = value;
+        } else {
+            self.cram[index - 1] = self.cram_latch;
+            self.cram[index] = value;
+        }
+    }
+
+    fn vram_address(&self) -> usize {
+        self.address as usize % VRAM_SIZE
+    }
+
+    fn increment_address(&mut self) {
+        self.address = (self.address + 1) & 0x3FFF;
+    }
+}
@@ -0,0 +1,192 @@
+use crate::vm::vdp::{
+    Vdp, VdpModel, SCREEN_HEIGHT, SCREEN_WIDTH, STATUS_SPRITE_COLLISION, STATUS_SPRITE_OVERFLOW,
+};
+
+const SPRITE_COUNT: usize = 64;
+const SPRITES_PER_LINE: usize = 8;
+const SPRITE_TERMINATOR: u8 = 0xD0;
+
+impl Vdp {
+    pub(crate) fn render_mode4_line(&mut self, line: usize) {
+        let mut line_buffer = [0u32; SCREEN_WIDTH];
+        let backdrop = self.mode4_colour(16 + (self.registers[7] & 0x0F) as usize);
+
+        if self.registers[1] & 0x40 == 0 {
+            for pixel in line_buffer.iter_mut() {
+                *pixel = backdrop;
+            }
+        } else {
+            let mut background = [0u8; SCREEN_WIDTH];
+            let mut priority = [false; SCREEN_WIDTH];
+            self.mode4_background(line, &mut background, &mut priority);
+            for x in 0..SCREEN_WIDTH {
+                line_buffer[x] = self.mode4_colour(background[x] as usize);
+            }
+            for x in 0..SCREEN_WIDTH {
+                let sprite = self.sprite_line[x];
+                if sprite != 0 && !priority[x] {
+                    line_buffer[x] = self.mode4_colour(16 + sprite as usize);
+                }
+            }
+            if self.registers[0] & 0x20 != 0 {
+                for pixel in line_buffer.iter_mut().take(8) {
+                    *pixel = backdrop;
+                }
+            }
+        }
+
+        self.framebuffer
+            .line_mut(line)
+            .copy_from_slice(&line_buffer);
+    }
+
+    fn mode4_background(&self, line: usize, colours: &mut [u8], priority: &mut [bool]) {
+        let extended = self.active_height() != SCREEN_HEIGHT;
+        let name_table = if extended {
+            ((self.registers[2] as usize & 0x0C) << 10) | 0x0700
+        } else {
+            (self.registers[2] as usize & 0x0E) << 10
+        };
+        let h_scroll = if self.registers[0] & 0x40 != 0 && line < 16 {
+            0
+        } else {
+            self.registers[8] as usize
+        };
+        let v_scroll = self.registers[9] as usize;
+        let rows = if extended { 256 } else { 224 };
+
+        for x in 0..SCREEN_WIDTH {
+            let locked = self.registers[0] & 0x80 != 0 && x >= 192;
+            let row = if locked {
+                line
+            } else {
+                (line + v_scroll) % rows
+            };
+            let column = (x + SCREEN_WIDTH - h_scroll) % SCREEN_WIDTH;
+            let mut entry_address = name_table + (row / 8) * 64 + (column / 8) * 2;
+            if self.model.masks_name_table() && self.registers[2] & 0x01 == 0 {
+                entry_address &= !0x0400;
+            }
+            let entry =
+                self.vram[entry_address] as u16 | (self.vram[entry_address + 1] as u16) << 8;
+
+            let pattern = (entry & 0x01FF) as usize;
+            let tile_x = if entry & 0x0200 != 0 {
+                7 - column % 8
+            } else {
+                column % 8
+            };
+            let tile_y = if entry & 0x0400 != 0 {
+                7 - row % 8
+            } else {
+                row % 8
+            };
+            let palette = if entry & 0x0800 != 0 { 16 } else { 0 };
+
+            let index = self.pattern_pixel(pattern * 32 + tile_y * 4, tile_x);
+            colours[x] = palette + index;
+            priority[x] = entry & 0x1000 != 0 && index != 0;
+        }
+    }
+
+    pub(crate) fn mode4_sprites(&mut self, line: usize) {
+        let table = (self.registers[5] as usize & 0x7E) << 7;
+        let patterns = if self.registers[6] & 0x04 != 0 {
+            0x2000
+        } else {
+            0
+        };
+        let tall = self.registers[1] & 0x02 != 0;
+        let zoom = if self.registers[1] & 0x01 != 0 { 2 } else { 1 };
+        let height = if tall { 16 } else { 8 };
+        let shift = if self.registers[0] & 0x08 != 0 { 8 } else { 0 };
+        let terminated = self.active_height() == SCREEN_HEIGHT;
+        let zoom_limit = if self.model.has_sprite_zoom_bug() {
+            4
+        } else {
+            SPRITES_PER_LINE
+        };
+
+        let mut drawn = [false; SCREEN_WIDTH];
+        let mut count = 0;
+        for sprite in 0..SPRITE_COUNT {
+            let y = self.vram[table + sprite];
+            if terminated && y == SPRITE_TERMINATOR {
+                break;
+            }
+            let row = (line as u8).wrapping_sub(y).wrapping_sub(1) as usize;
+            if row >= height * zoom {
+                continue;
+            }
+            count += 1;
+            if count > SPRITES_PER_LINE {
+                self.status |= STATUS_SPRITE_OVERFLOW;
+                break;
+            }
+
+            let x = self.vram[table + 128 + sprite * 2] as isize - shift;
+            let mut pattern = self.vram[table + 129 + sprite * 2] as usize;
+            if tall {
+                pattern &= 0xFE;
+            }
+            let tile_y = row / zoom;
+            let address = patterns + (pattern + tile_y / 8) * 32 + (tile_y % 8) * 4;
+            let width = if count > zoom_limit { 1 } else { zoom };
+
+            for offset in 0..8 * width {
+                let screen_x = x + offset as isize;
+                if screen_x < 0 || screen_x >= SCREEN_WIDTH as isize {
+                    continue;
+                }
+                let screen_x = screen_x as usize;
+                let index = self.pattern_pixel(address & 0x3FFF, offset / width);
+                if index == 0 {
+                    continue;
+                }
+                if drawn[screen_x] {
+                    self.status |= STATUS_SPRITE_COLLISION;
+                    continue;
+                }
+                drawn[screen_x] = true;
+                self.sprite_line[screen_x] = index;
+            }
+        }
+    }
+
+    fn pattern_pixel(&self, address: usize, x: usize) -> u8 {
+        let bit = 7 - x;
+        (0..4).fold(0, |acc, plane| {
+            acc | ((self.vram[address + plane] >> bit) & 1) << plane
+        })
+    }
+
+    pub(crate) fn mode4_colour(&self, index: usize) -> u32 {
+        if self.model == VdpModel::GameGear && !self.sms_mode {
+            let value = self.cram[index * 2] as u32 | (self.cram[index * 2 + 1] as u32) << 8;
+            game_gear_colour(value)
+        } else if self.model == VdpModel::GameGear {
+            game_gear_colour(legacy_colour(self.cram[index]))
+        } else {
+            let value = self.cram[index] as u32;
+            let red = (value & 0x03) * 85;
+            let green = ((value >> 2) & 0x03) * 85;
+            let blue = ((value >> 4) & 0x03) * 85;
+            (red << 16) | (green << 8) | blue
+        }
+    }
+}
+
+fn game_gear_colour(value: u32) -> u32 {
+    let red = (value & 0x0F) * 17;
+    let green = ((value >> 4) & 0x0F) * 17;
+    let blue = ((value >> 8) & 0x0F) * 17;
+    (red << 16) | (green << 8) | blue
+}
+
+// Expands each 2-bit Master System component to the 4-bit Game Gear range.
+fn legacy_colour(value: u8) -> u32 {
+    (0..3).fold(0, |colour, component| {
+        let level = ((value >> (component * 2)) & 0x03) as u32;
+        colour | (level * 5) << (component * 4)
+    })
+}
@@ -0,0 +1,101 @@
+use crate::vm::vdp::{Vdp, SCREEN_WIDTH};
+
+// The VDP only grants the CPU one VRAM access slot every 26 cycles while it is fetching the
+// active display, so faster writes wait for the next free slot.
+const ACCESS_SLOT_CYCLES: u64 = 26;
+const H_COUNTER_OFFSET: usize = 0x14;
+// 0x00-0x93 and 0xE9-0xFF: one count for every two of the line's 342 pixels.
+const H_COUNTER_STEPS: usize = 171;
+
+impl Vdp {
+    pub fn accurate_timing(&self) -> bool {
+        self.accurate_timing
+    }
+
+    pub fn set_accurate_timing(&mut self, enabled: bool) {
+        self.accurate_timing = enabled;
+    }
+
+    pub fn delayed_writes(&self) -> u64 {
+        self.delayed_writes
+    }
+
+    pub(crate) fn synchronize(&mut self, cycle: u64, line_cycle: u64) {
+        self.cycle = cycle;
+        self.line_cycle = line_cycle;
+        while let Some(&(slot, index, value)) = self.pending_writes.front() {
+            if slot > cycle {
+                break;
+            }
+            self.vram[index] = value;
+            self.pending_writes.pop_front();
+        }
+    }
+
+    // The H counter while the beam is `x` pixels past the start of the active display. It
+    // advances once every two pixels from 0x14 at the first pixel and jumps from 0x93 to 0xE9
+    // during the horizontal blank. The Light Phaser latches the same counter.
+    pub(crate) fn h_counter_at(x: usize) -> u8 {
+        let counter = (x / 2 + H_COUNTER_OFFSET) % H_COUNTER_STEPS;
+        if counter > 0x93 {
+            (counter + 0xE9 - 0x94) as u8
+        } else {
+            counter as u8
+        }
+    }
+
+    pub(crate) fn current_h_counter(&self) -> u8 {
+        Vdp::h_counter_at(self.line_cycle as usize * 3 / 2)
+    }
+
+    // The beam draws three pixels every two CPU cycles.
+    pub(crate) fn beam_x(&self) -> usize {
+        (self.line_cycle as usize * 3 / 2).min(SCREEN_WIDTH)
+    }
+
+    pub(crate) fn catch_up(&mut self) {
+        if self.accurate_timing && (self.line as usize) < self.active_height() {
+            self.render_span(self.beam_x());
+        }
+    }
+
+    pub(crate) fn render_span(&mut self, end: usize) {
+        let line = self.line as usize;
+        let start = self.drawn_x;
+        if start >= end {
+            return;
+        }
+        if start == 0 {
+            self.evaluate_sprites(line);
+        }
+        let drawn = self.framebuffer.line_mut(line)[..start].to_vec();
+        self.render_line(line);
+        self.framebuffer.line_mut(line)[..start].copy_from_slice(&drawn);
+        self.drawn_x = end;
+    }
+
+    // Writes that arrive before the next slot are queued and land in VRAM once `synchronize`
+    // reaches their slot.
+    pub(crate) fn write_vram(&mut self, index: usize, value: u8) {
+        let active = (self.line as usize) < self.active_height() && self.registers[1] & 0x40 != 0;
+        if !self.accurate_timing || !active {
+            self.flush_pending_writes();
+            self.vram[index] = value;
+            return;
+        }
+        let slot = self.cycle.max(self.next_access_slot);
+        self.next_access_slot = slot + ACCESS_SLOT_CYCLES;
+        if slot > self.cycle {
+            self.delayed_writes += 1;
+            self.pending_writes.push_back((slot, index, value));
+        } else {
+            self.vram[index] = value;
+        }
+    }
+
+    pub(crate) fn flush_pending_writes(&mut self) {
+        for (_, index, value) in self.pending_writes.drain(..) {
+            self.vram[index] = value;
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::vm::vdp::{
+    DisplayMode, Vdp, SCREEN_WIDTH, STATUS_SPRITE_COLLISION, STATUS_SPRITE_OVERFLOW,
+};
+
+const SPRITE_COUNT: usize = 32;
+const SPRITES_PER_LINE: usize = 4;
+const SPRITE_TERMINATOR: u8 = 0xD0;
+const TEXT_BORDER: usize = 8;
+
+const PALETTE: [u32; 16] = [
+    0x000000, 0x000000, 0x21C842, 0x5EDC78, 0x5455ED, 0x7D76FC, 0xD4524D, 0x42EBF5, 0xFC5554,
+    0xFF7978, 0xD4C154, 0xE6CE80, 0x21B03B, 0xC95BBA, 0xCCCCCC, 0xFFFFFF,
+];
+
+impl Vdp {
+    pub(crate) fn render_tms_line(&mut self, line: usize) {
+        let backdrop = (self.registers[7] & 0x0F) as usize;
+        let mut colours = [backdrop as u8; SCREEN_WIDTH];
+
+        if self.registers[1] & 0x40 != 0 {
+            match self.display_mode() {
+                DisplayMode::Graphics1 => self.graphics1_line(line, &mut colours),
+                DisplayMode::Graphics2 => self.graphics2_line(line, &mut colours),
+                DisplayMode::Multicolor => self.multicolor_line(line, &mut colours),
+                DisplayMode::Text => self.text_line(line, &mut colours),
+                _ => {}
+            }
+            for (colour, sprite) in colours.iter_mut().zip(self.sprite_line.iter()) {
+                if *sprite != 0 {
+                    *colour = *sprite;
+                }
+            }
+        }
+
+        let output = self.framebuffer.line_mut(line);
+        for (pixel, colour) in output.iter_mut().zip(colours.iter()) {
+            let index = if *colour == 0 {
+                backdrop
+            } else {
+                *colour as usize
+            };
+            *pixel = PALETTE[index];
+        }
+    }
+
+    fn graphics1_line(&self, line: usize, colours: &mut [u8]) {
+        let names = (self.registers[2] as usize & 0x0F) << 10;
+        let table = self.registers[3] as usize * 0x40;
+        let patterns = (self.registers[4] as usize & 0x07) << 11;
+        for column in 0..32 {
+            let name = self.vram[names + (line / 8) * 32 + column] as usize;
+            let pattern = self.vram[patterns + name * 8 + line % 8];
+            let colour = self.vram[table + name / 8];
+            self.tms_pattern_row(pattern, colour, &mut colours[column * 8..column * 8 + 8]);
+        }
+    }
+
+    fn graphics2_line(&self, line: usize, colours: &mut [u8]) {
+        let names = (self.registers[2] as usize & 0x0F) << 10;
+        let table = (self.registers[3] as usize & 0x80) << 6;
+        let table_mask = ((self.registers[3] as usize & 0x7F) << 6) | 0x3F;
+        let patterns = (self.registers[4] as usize & 0x04) << 11;
+        let pattern_mask = ((self.registers[4] as usize & 0x03) << 11) | 0x07FF;
+        for column in 0..32 {
+            let name = self.vram[names + (line / 8) * 32 + column] as usize;
+            let offset = ((line / 64) * 256 + name) * 8 + line % 8;
+            let pattern = self.vram[patterns | (offset & pattern_mask)];
+            let colour = self.vram[table | (offset & table_mask)];
+            self.tms_pattern_row(pattern, colour, &mut colours[column * 8..column * 8 + 8]);
+        }
+    }
+
+    fn multicolor_line(&self, line: usize, colours: &mut [u8]) {
+        let names = (self.registers[2] as usize & 0x0F) << 10;
+        let patterns = (self.registers[4] as usize & 0x07) << 11;
+        for column in 0..32 {
+            let name = self.vram[names + (line / 8) * 32 + column] as usize;
+            let colour = self.vram[patterns + name * 8 + ((line / 8) % 4) * 2 + (line % 8) / 4];
+            for x in 0..8 {
+                colours[column * 8 + x] = if x < 4 { colour >> 4 } else { colour & 0x0F };
+            }
+        }
+    }
+
+    fn text_line(&self, line: usize, colours: &mut [u8]) {
+        let names = (self.registers[2] as usize & 0x0F) << 10;
+        let patterns = (self.registers[4] as usize & 0x07) << 11;
+        let colour = self.registers[7];
+        for column in 0..40 {
+            let name = self.vram[names + (line / 8) * 40 + column] as usize;
+            let pattern = self.vram[patterns + name * 8 + line % 8];
+            let start = TEXT_BORDER + column * 6;
+            self.tms_pattern_row(pattern, colour, &mut colours[start..start + 6]);
+        }
+    }
+
+    fn tms_pattern_row(&self, pattern: u8, colour: u8, output: &mut [u8]) {
+        for (x, pixel) in output.iter_mut().enumerate() {
+            *pixel = if pattern & (0x80 >> x) != 0 {
+                colour >> 4
+            } else {
+                colour & 0x0F
+            };
+        }
+    }
+
+    pub(crate) fn tms_sprites(&mut self, line: usize) {
+        let table = (self.registers[5] as usize & 0x7F) << 7;
+        let patterns = (self.registers[6] as usize & 0x07) << 11;
+        let large = self.registers[1] & 0x02 != 0;
+        let zoom = if self.registers[1] & 0x01 != 0 { 2 } else { 1 };
+        let size = if large { 16 } else { 8 };
+
+        let mut drawn = [false; SCREEN_WIDTH];
+        let mut count = 0;
+        let mut last = SPRITE_COUNT - 1;
+        for sprite in 0..SPRITE_COUNT {
+            let entry = table + sprite * 4;
+            let y = self.vram[entry];
+            if y == SPRITE_TERMINATOR {
+                last = sprite;
+                break;
+            }
+            let row = (line as u8).wrapping_sub(y).wrapping_sub(1) as usize;
+            if row >= size * zoom {
+                continue;
+            }
+            count += 1;
+            if count > SPRITES_PER_LINE {
+                if self.status & STATUS_SPRITE_OVERFLOW == 0 {
+                    self.status = (self.status & 0xE0) | STATUS_SPRITE_OVERFLOW | sprite as u8;
+                }
+                return;
+            }
+
+            let attributes = self.vram[entry + 3];
+            let early = if attributes & 0x80 != 0 { 32 } else { 0 };
+            let x = self.vram[entry + 1] as isize - early;
+            let pattern = if large {
+                self.vram[entry + 2] & 0xFC
+            } else {
+                self.vram[entry + 2]
+            } as usize;
+            let colour = attributes & 0x0F;
+            let tile_y = row / zoom;
+
+            for offset in 0..size * zoom {
+                let screen_x = x + offset as isize;
+                if screen_x < 0 || screen_x >= SCREEN_WIDTH as isize {
+                    continue;
+                }
+                let screen_x = screen_x as usize;
+                let tile_x = offset / zoom;
+                let address = patterns + pattern * 8 + (tile_x / 8) * 16 + tile_y;
+                if self.vram[address] & (0x80 >> (tile_x % 8)) == 0 {
+                    continue;
+                }
+                if drawn[screen_x] {
+                    self.status |= STATUS_SPRITE_COLLISION;
+                    continue;
+                }
+                drawn[screen_x] = true;
+                self.sprite_line[screen_x] = colour;
+            }
+        }
+        if self.status & STATUS_SPRITE_OVERFLOW == 0 {
+            self.status = (self.status & 0xE0) | last as u8;
+        }
+    }
+}
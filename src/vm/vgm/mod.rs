@@ -0,0 +1,82 @@
+pub mod player;
+pub mod recorder;
+
+use crate::vm::machine::Machine;
+use crate::vm::vgm::recorder::VgmRecorder;
+
+pub const SAMPLE_RATE: u32 = 44_100;
+pub const VERSION: u32 = 0x0000_0151;
+pub const HEADER_SIZE: usize = 0x80;
+
+pub const COMMAND_GAME_GEAR_STEREO: u8 = 0x4F;
+pub const COMMAND_PSG: u8 = 0x50;
+pub const COMMAND_YM2413: u8 = 0x51;
+pub const COMMAND_WAIT: u8 = 0x61;
+pub const COMMAND_WAIT_NTSC_FRAME: u8 = 0x62;
+pub const COMMAND_WAIT_PAL_FRAME: u8 = 0x63;
+pub const COMMAND_END: u8 = 0x66;
+pub const COMMAND_WAIT_SHORT: u8 = 0x70;
+
+#[derive(Clone, Default, Debug)]
+pub struct Gd3Tags {
+    pub track: String,
+    pub track_japanese: String,
+    pub game: String,
+    pub game_japanese: String,
+    pub system: String,
+    pub system_japanese: String,
+    pub author: String,
+    pub author_japanese: String,
+    pub release_date: String,
+    pub ripper: String,
+    pub notes: String,
+}
+
+impl Gd3Tags {
+    pub fn fields(&self) -> [&str; 11] {
+        [
+            &self.track,
+            &self.track_japanese,
+            &self.game,
+            &self.game_japanese,
+            &self.system,
+            &self.system_japanese,
+            &self.author,
+            &self.author_japanese,
+            &self.release_date,
+            &self.ripper,
+            &self.notes,
+        ]
+    }
+}
+
+impl Machine {
+    pub fn is_logging_vgm(&self) -> bool {
+        self.vgm.is_some()
+    }
+
+    pub fn start_vgm_log(&mut self) {
+        let tv_system = self.tv_system();
+        let mut recorder = VgmRecorder::new(self.cycles, tv_system, self.fm.is_some());
+        recorder.capture_psg(self.cycles, &self.psg);
+        if self.game_gear_mode() {
+            recorder.game_gear_stereo(self.cycles, self.psg.stereo());
+        }
+        if let Some(fm) = self.fm.as_ref() {
+            recorder.capture_ym2413(self.cycles, fm);
+        }
+        self.vgm = Some(recorder);
+    }
+
+    pub fn mark_vgm_loop(&mut self) {
+        let cycles = self.cycles;
+        if let Some(recorder) = self.vgm.as_mut() {
+            recorder.mark_loop(cycles);
+        }
+    }
+
+    pub fn stop_vgm_log(&mut self) -> Option<Vec<u8>> {
+        let cycles = self.cycles;
+        self.vgm.take().map(|recorder| recorder.finish(cycles))
+    }
+}
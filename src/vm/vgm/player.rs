@@ -0,0 +1,317 @@
+use crate::vm::audio::stereo_buffer::StereoBuffer;
+use crate::vm::machine::DEFAULT_SAMPLE_RATE;
+use crate::vm::psg::{Psg, SEGA_LFSR_TAPS, SEGA_LFSR_WIDTH};
+use crate::vm::vgm::{
+    Gd3Tags, COMMAND_END, COMMAND_GAME_GEAR_STEREO, COMMAND_PSG, COMMAND_WAIT,
+    COMMAND_WAIT_NTSC_FRAME, COMMAND_WAIT_PAL_FRAME, COMMAND_WAIT_SHORT, COMMAND_YM2413,
+    SAMPLE_RATE,
+};
+use crate::vm::ym2413::Ym2413;
+
+const LEGACY_DATA_OFFSET: usize = 0x40;
+const COMMAND_DATA_BLOCK: u8 = 0x67;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum VgmError {
+    InvalidHeader,
+    Compressed,
+    NoSupportedChips,
+    UnexpectedEnd,
+    UnknownCommand(u8),
+    UnsupportedNoise,
+}
+
+pub struct VgmPlayer {
+    data: Vec<u8>,
+    position: usize,
+    loop_offset: Option<usize>,
+    clock: u32,
+    total_samples: u32,
+    loop_samples: u32,
+    loops_remaining: u32,
+    tags: Gd3Tags,
+    psg: Psg,
+    fm: Option<Ym2413>,
+    audio: StereoBuffer,
+    samples: u64,
+    pending_wait: u64,
+    finished: bool,
+}
+
+impl VgmPlayer {
+    pub fn new(data: &[u8]) -> Result<VgmPlayer, VgmError> {
+        if data.starts_with(&[0x1F, 0x8B]) {
+            return Err(VgmError::Compressed);
+        }
+        if data.len() < LEGACY_DATA_OFFSET || &data[0..4] != b"Vgm " {
+            return Err(VgmError::InvalidHeader);
+        }
+        let version = read_u32(data, 0x08);
+        let psg_clock = read_u32(data, 0x0C) & 0x3FFF_FFFF;
+        let ym2413_clock = read_u32(data, 0x10) & 0x3FFF_FFFF;
+        let clock = if psg_clock != 0 {
+            psg_clock
+        } else {
+            ym2413_clock
+        };
+        if clock == 0 {
+            return Err(VgmError::NoSupportedChips);
+        }
+
+        let position = match read_u32(data, 0x34) {
+            offset if version >= 0x150 && offset != 0 => 0x34 + offset as usize,
+            _ => LEGACY_DATA_OFFSET,
+        };
+        let loop_offset = match read_u32(data, 0x1C) {
+            0 => None,
+            offset => Some(0x1C + offset as usize),
+        };
+        let tags = match read_u32(data, 0x14) {
+            0 => Gd3Tags::default(),
+            offset => read_gd3(data, 0x14 + offset as usize).unwrap_or_default(),
+        };
+        if position > data.len() {
+            return Err(VgmError::UnexpectedEnd);
+        }
+        let mut psg = Psg::new();
+        if version >= 0x110 && psg_clock != 0 {
+            let (taps, width) = read_noise_shape(data)?;
+            psg.set_noise_shape(taps, width);
+        }
+
+        Ok(VgmPlayer {
+            data: data.to_vec(),
+            position,
+            loop_offset,
+            clock,
+            total_samples: read_u32(data, 0x18),
+            loop_samples: read_u32(data, 0x20),
+            loops_remaining: 0,
+            tags,
+            psg,
+            fm: if ym2413_clock != 0 {
+                let mut fm = Ym2413::with_clock(ym2413_clock);
+                fm.set_cycle_rate(Some(clock));
+                Some(fm)
+            } else {
+                None
+            },
+            audio: StereoBuffer::new(clock, DEFAULT_SAMPLE_RATE),
+            samples: 0,
+            pending_wait: 0,
+            finished: false,
+        })
+    }
+
+    pub fn tags(&self) -> &Gd3Tags {
+        &self.tags
+    }
+
+    pub fn clock(&self) -> u32 {
+        self.clock
+    }
+
+    pub fn has_ym2413(&self) -> bool {
+        self.fm.is_some()
+    }
+
+    pub fn total_samples(&self) -> u32 {
+        self.total_samples
+    }
+
+    pub fn loop_samples(&self) -> u32 {
+        self.loop_samples
+    }
+
+    pub fn set_loop_count(&mut self, loops: u32) {
+        self.loops_remaining = loops;
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.audio.sample_rate()
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.audio.set_sample_rate(sample_rate);
+    }
+
+    pub fn psg(&self) -> &Psg {
+        &self.psg
+    }
+
+    pub fn fm(&self) -> Option<&Ym2413> {
+        self.fm.as_ref()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Renders the next `duration` samples of the log, counted at the VGM rate of 44100 Hz, and
+    // returns interleaved stereo frames at the output sample rate.
+    pub fn render(&mut self, duration: u32) -> Result<Vec<i16>, VgmError> {
+        let target = self.samples + duration as u64;
+        while !self.finished && self.samples < target {
+            if self.pending_wait > 0 {
+                let wait = self.pending_wait.min(target - self.samples);
+                self.samples += wait;
+                self.pending_wait -= wait;
+            } else if let Some(wait) = self.step()? {
+                self.pending_wait = wait as u64;
+            }
+        }
+        let cycle = self.cycle(target);
+        self.run_until(cycle);
+        self.samples = target;
+        self.audio.end_frame(cycle);
+        Ok(self.audio.take_samples())
+    }
+
+    pub fn render_to_end(&mut self) -> Result<Vec<i16>, VgmError> {
+        let mut output = Vec::new();
+        while !self.finished {
+            output.extend(self.render(SAMPLE_RATE)?);
+        }
+        Ok(output)
+    }
+
+    fn cycle(&self, samples: u64) -> u64 {
+        samples * self.clock as u64 / SAMPLE_RATE as u64
+    }
+
+    fn run_until(&mut self, cycle: u64) {
+        self.psg.run_until(cycle, &mut self.audio);
+        if let Some(fm) = self.fm.as_mut() {
+            fm.run_until(cycle, &mut self.audio);
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<u32>, VgmError> {
+        let cycle = self.cycle(self.samples);
+        let command = self.byte(0)?;
+        let wait = match command {
+            COMMAND_GAME_GEAR_STEREO => {
+                let value = self.byte(1)?;
+                self.psg.write_stereo(cycle, value, &mut self.audio);
+                None
+            }
+            COMMAND_PSG => {
+                let value = self.byte(1)?;
+                self.psg.write(cycle, value, &mut self.audio);
+                None
+            }
+            COMMAND_YM2413 => {
+                let (register, value) = (self.byte(1)?, self.byte(2)?);
+                if let Some(fm) = self.fm.as_mut() {
+                    fm.write_address(register);
+                    fm.write_data(cycle, value, &mut self.audio);
+                }
+                None
+            }
+            COMMAND_WAIT => Some(self.byte(1)? as u32 | (self.byte(2)? as u32) << 8),
+            COMMAND_WAIT_NTSC_FRAME => Some(735),
+            COMMAND_WAIT_PAL_FRAME => Some(882),
+            0x70..=0x7F => Some((command - COMMAND_WAIT_SHORT) as u32 + 1),
+            0x80..=0x8F => Some((command & 0x0F) as u32),
+            COMMAND_END => {
+                match (self.loop_offset, self.loops_remaining) {
+                    (Some(offset), loops) if loops > 0 => {
+                        self.loops_remaining -= 1;
+                        self.position = offset;
+                    }
+                    _ => self.finished = true,
+                }
+                return Ok(None);
+            }
+            _ => None,
+        };
+        self.position += self.command_length(command)?;
+        Ok(wait)
+    }
+
+    // Commands for chips this crate does not emulate are skipped using the lengths reserved for
+    // each command range.
+    fn command_length(&self, command: u8) -> Result<usize, VgmError> {
+        let length = match command {
+            COMMAND_GAME_GEAR_STEREO | COMMAND_PSG => 2,
+            COMMAND_YM2413 | COMMAND_WAIT => 3,
+            COMMAND_WAIT_NTSC_FRAME | COMMAND_WAIT_PAL_FRAME => 1,
+            COMMAND_DATA_BLOCK => 7 + read_u32(&self.data, self.position + 3) as usize,
+            0x30..=0x3F => 2,
+            0x40..=0x4E | 0x52..=0x5F => 3,
+            0x70..=0x8F => 1,
+            0x90 | 0x91 | 0x95 => 5,
+            0x92 => 6,
+            0x93 => 11,
+            0x94 => 2,
+            0xA0..=0xBF => 3,
+            0xC0..=0xDF => 4,
+            0xE0..=0xFF => 5,
+            _ => return Err(VgmError::UnknownCommand(command)),
+        };
+        if self.position + length > self.data.len() {
+            return Err(VgmError::UnexpectedEnd);
+        }
+        Ok(length)
+    }
+
+    fn byte(&self, offset: usize) -> Result<u8, VgmError> {
+        self.data
+            .get(self.position + offset)
+            .cloned()
+            .ok_or(VgmError::UnexpectedEnd)
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0; 4];
+    if let Some(slice) = data.get(offset..offset + 4) {
+        bytes.copy_from_slice(slice);
+    }
+    u32::from_le_bytes(bytes)
+}
+
+// Files from version 1.10 on describe the SN76489 variant's noise shift register, with zero
+// standing for the Sega chip.
+fn read_noise_shape(data: &[u8]) -> Result<(u16, u8), VgmError> {
+    let taps = match u16::from_le_bytes([data[0x28], data[0x29]]) {
+        0 => SEGA_LFSR_TAPS,
+        taps => taps,
+    };
+    let width = match data[0x2A] {
+        0 => SEGA_LFSR_WIDTH,
+        width => width,
+    };
+    if width > 16 || u32::from(taps) >> width != 0 {
+        return Err(VgmError::UnsupportedNoise);
+    }
+    Ok((taps, width))
+}
+
+fn read_gd3(data: &[u8], offset: usize) -> Option<Gd3Tags> {
+    if data.get(offset..offset + 4)? != b"Gd3 " {
+        return None;
+    }
+    let length = read_u32(data, offset + 8) as usize;
+    let text = data.get(offset + 12..offset + 12 + length)?;
+    let units: Vec<u16> = text
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    let mut fields = units.split(|unit| *unit == 0).map(String::from_utf16_lossy);
+    let mut next = || fields.next().unwrap_or_default();
+    Some(Gd3Tags {
+        track: next(),
+        track_japanese: next(),
+        game: next(),
+        game_japanese: next(),
+        system: next(),
+        system_japanese: next(),
+        author: next(),
+        author_japanese: next(),
+        release_date: next(),
+        ripper: next(),
+        notes: next(),
+    })
+}
@@ -0,0 +1,154 @@
+use crate::vm::psg::{Psg, CHANNEL_COUNT, NOISE_CHANNEL, SEGA_LFSR_TAPS, SEGA_LFSR_WIDTH};
+use crate::vm::vdp::TvSystem;
+use crate::vm::vgm::{
+    Gd3Tags, COMMAND_END, COMMAND_GAME_GEAR_STEREO, COMMAND_PSG, COMMAND_WAIT,
+    COMMAND_WAIT_NTSC_FRAME, COMMAND_WAIT_PAL_FRAME, COMMAND_WAIT_SHORT, COMMAND_YM2413,
+    HEADER_SIZE, SAMPLE_RATE, VERSION,
+};
+use crate::vm::ym2413::Ym2413;
+
+const YM2413_REGISTERS: [std::ops::Range<u8>; 3] = [0x00..0x08, 0x0E..0x0F, 0x10..0x39];
+
+pub struct VgmRecorder {
+    pub tags: Gd3Tags,
+    tv_system: TvSystem,
+    fm: bool,
+    start_cycle: u64,
+    samples: u64,
+    commands: Vec<u8>,
+    loop_point: Option<(usize, u64)>,
+}
+
+impl VgmRecorder {
+    pub fn new(cycle: u64, tv_system: TvSystem, fm: bool) -> VgmRecorder {
+        VgmRecorder {
+            tags: Gd3Tags::default(),
+            tv_system,
+            fm,
+            start_cycle: cycle,
+            samples: 0,
+            commands: Vec::new(),
+            loop_point: None,
+        }
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn psg(&mut self, cycle: u64, value: u8) {
+        self.wait_until(cycle);
+        self.commands.extend_from_slice(&[COMMAND_PSG, value]);
+    }
+
+    pub fn game_gear_stereo(&mut self, cycle: u64, value: u8) {
+        self.wait_until(cycle);
+        self.commands
+            .extend_from_slice(&[COMMAND_GAME_GEAR_STEREO, value]);
+    }
+
+    pub fn ym2413(&mut self, cycle: u64, register: u8, value: u8) {
+        self.wait_until(cycle);
+        self.commands
+            .extend_from_slice(&[COMMAND_YM2413, register, value]);
+    }
+
+    // A log started mid-song begins with the current chip state so that it plays back correctly.
+    pub fn capture_psg(&mut self, cycle: u64, psg: &Psg) {
+        for channel in 0..CHANNEL_COUNT {
+            let select = (channel as u8) << 5;
+            if channel == NOISE_CHANNEL {
+                self.psg(cycle, 0xE0 | psg.noise_control());
+            } else {
+                let period = psg.tone_period(channel);
+                self.psg(cycle, 0x80 | select | (period as u8 & 0x0F));
+                self.psg(cycle, (period >> 4) as u8 & 0x3F);
+            }
+            self.psg(cycle, 0x90 | select | psg.attenuation(channel));
+        }
+    }
+
+    pub fn capture_ym2413(&mut self, cycle: u64, fm: &Ym2413) {
+        for range in YM2413_REGISTERS.iter() {
+            for register in range.clone() {
+                self.ym2413(cycle, register, fm.register(register));
+            }
+        }
+    }
+
+    pub fn mark_loop(&mut self, cycle: u64) {
+        self.wait_until(cycle);
+        self.loop_point = Some((self.commands.len(), self.samples));
+    }
+
+    pub fn finish(mut self, cycle: u64) -> Vec<u8> {
+        self.wait_until(cycle);
+        self.commands.push(COMMAND_END);
+
+        let gd3 = self.gd3();
+        let mut file = vec![0; HEADER_SIZE];
+        file.extend_from_slice(&self.commands);
+        let gd3_offset = file.len();
+        file.extend_from_slice(&gd3);
+
+        let clock = self.tv_system.cpu_clock();
+        let rate = match self.tv_system {
+            TvSystem::Ntsc => 60,
+            TvSystem::Pal => 50,
+        };
+        write_u32(&mut file, 0x00, u32::from_le_bytes(*b"Vgm "));
+        let length = file.len();
+        write_u32(&mut file, 0x04, (length - 0x04) as u32);
+        write_u32(&mut file, 0x08, VERSION);
+        write_u32(&mut file, 0x0C, clock);
+        write_u32(&mut file, 0x10, if self.fm { clock } else { 0 });
+        write_u32(&mut file, 0x14, (gd3_offset - 0x14) as u32);
+        write_u32(&mut file, 0x18, self.samples as u32);
+        if let Some((position, samples)) = self.loop_point {
+            write_u32(&mut file, 0x1C, (HEADER_SIZE + position - 0x1C) as u32);
+            write_u32(&mut file, 0x20, (self.samples - samples) as u32);
+        }
+        write_u32(&mut file, 0x24, rate);
+        file[0x28..0x2A].copy_from_slice(&SEGA_LFSR_TAPS.to_le_bytes());
+        file[0x2A] = SEGA_LFSR_WIDTH;
+        write_u32(&mut file, 0x34, (HEADER_SIZE - 0x34) as u32);
+        file
+    }
+
+    fn gd3(&self) -> Vec<u8> {
+        let mut text = Vec::new();
+        for field in self.tags.fields().iter() {
+            for unit in field.encode_utf16().chain(std::iter::once(0)) {
+                text.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+        let mut gd3 = b"Gd3 ".to_vec();
+        gd3.extend_from_slice(&0x0000_0100u32.to_le_bytes());
+        gd3.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        gd3.extend_from_slice(&text);
+        gd3
+    }
+
+    fn wait_until(&mut self, cycle: u64) {
+        let elapsed = cycle.saturating_sub(self.start_cycle);
+        let target = elapsed * SAMPLE_RATE as u64 / self.tv_system.cpu_clock() as u64;
+        while self.samples < target {
+            let wait = (target - self.samples).min(0xFFFF);
+            match wait {
+                735 => self.commands.push(COMMAND_WAIT_NTSC_FRAME),
+                882 => self.commands.push(COMMAND_WAIT_PAL_FRAME),
+                1..=16 => self.commands.push(COMMAND_WAIT_SHORT + wait as u8 - 1),
+                _ => {
+                    self.commands.push(COMMAND_WAIT);
+                    self.commands
+                        .extend_from_slice(&(wait as u16).to_le_bytes());
+                }
+            }
+            self.samples += wait;
+        }
+    }
+}
+
+fn write_u32(file: &mut [u8], offset: usize, value: u32) {
+    file[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
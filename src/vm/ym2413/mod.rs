@@ -0,0 +1,389 @@
+mod operator;
+mod patches;
+
+use std::f64::consts::PI;
+
+use crate::vm::audio::stereo_buffer::StereoBuffer;
+use crate::vm::ym2413::operator::{EnvelopeState, Operator};
+use crate::vm::ym2413::patches::{Patch, BASS_DRUM_PATCH, ROM, USER_PATCH};
+
+pub const CHANNEL_COUNT: usize = 9;
+pub const RHYTHM_CHANNEL: usize = 6;
+
+const REGISTER_COUNT: usize = 0x40;
+const CLOCK_DIVIDER: u64 = 72;
+const DEFAULT_CLOCK: u32 = 3_579_545;
+const CHANNEL_AMPLITUDE: f64 = 2048.0;
+const MODULATION_INDEX: f64 = 4.0 * PI;
+const TREMOLO_RATE: f64 = 3.7;
+const TREMOLO_DEPTH: f64 = 4.8;
+const VIBRATO_RATE: f64 = 6.4;
+const VIBRATO_CENTS: f64 = 7.0;
+const NOISE_TAPS: u32 = 0x0080_0302;
+
+const RHYTHM_ENABLE: u8 = 0x20;
+const BASS_DRUM: u8 = 0x10;
+const SNARE_DRUM: u8 = 0x08;
+const TOM_TOM: u8 = 0x04;
+const CYMBAL: u8 = 0x02;
+const HI_HAT: u8 = 0x01;
+
+pub struct Ym2413 {
+    registers: [u8; REGISTER_COUNT],
+    address: u8,
+    modulators: [Operator; CHANNEL_COUNT],
+    carriers: [Operator; CHANNEL_COUNT],
+    patches: [Patch; CHANNEL_COUNT],
+    clock: u32,
+    cycle_rate: Option<u32>,
+    tick_remainder: u64,
+    tremolo_phase: f64,
+    vibrato_phase: f64,
+    noise: u32,
+    muted: bool,
+    mute_mask: u16,
+    next_tick: u64,
+    amplitude: i32,
+}
+
+impl Default for Ym2413 {
+    fn default() -> Self {
+        Ym2413::new()
+    }
+}
+
+impl Ym2413 {
+    pub fn new() -> Ym2413 {
+        Ym2413::with_clock(DEFAULT_CLOCK)
+    }
+
+    // The unit shares the CPU clock, so it runs slightly slower on PAL consoles.
+    pub fn with_clock(clock: u32) -> Ym2413 {
+        Ym2413 {
+            registers: [0; REGISTER_COUNT],
+            address: 0,
+            modulators: [Operator::new(); CHANNEL_COUNT],
+            carriers: [Operator::new(); CHANNEL_COUNT],
+            patches: [Patch::from_bytes(&ROM[USER_PATCH]); CHANNEL_COUNT],
+            clock,
+            cycle_rate: None,
+            tick_remainder: 0,
+            tremolo_phase: 0.0,
+            vibrato_phase: 0.0,
+            noise: 1,
+            muted: false,
+            mute_mask: 0,
+            next_tick: 0,
+            amplitude: 0,
+        }
+    }
+
+    pub fn reset(&mut self, cycle: u64, output: &mut StereoBuffer) {
+        *self = Ym2413 {
+            next_tick: self.next_tick,
+            cycle_rate: self.cycle_rate,
+            tick_remainder: self.tick_remainder,
+            amplitude: self.amplitude,
+            muted: self.muted,
+            mute_mask: self.mute_mask,
+            ..Ym2413::with_clock(self.clock)
+        };
+        self.update_output(cycle, 0, output);
+    }
+
+    pub fn clock(&self) -> u32 {
+        self.clock
+    }
+
+    pub fn set_clock(&mut self, clock: u32) {
+        self.clock = clock;
+    }
+
+    pub fn cycle_rate(&self) -> Option<u32> {
+        self.cycle_rate
+    }
+
+    // Cycles passed to the chip are counted at its own clock unless another rate is given, as
+    // when a VGM log times both chips by the PSG's clock.
+    pub fn set_cycle_rate(&mut self, rate: Option<u32>) {
+        self.cycle_rate = rate;
+    }
+
+    pub fn register(&self, index: u8) -> u8 {
+        self.registers[index as usize % REGISTER_COUNT]
+    }
+
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    pub fn rhythm_mode(&self) -> bool {
+        self.registers[0x0E] & RHYTHM_ENABLE != 0
+    }
+
+    pub fn is_sounding(&self, channel: usize) -> bool {
+        self.carriers[channel].state() != EnvelopeState::Off
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn mute_mask(&self) -> u16 {
+        self.mute_mask
+    }
+
+    // In rhythm mode the drums follow the bits of the channels they replace: the bass drum is
+    // channel 6, the hi-hat and snare channel 7, and the tom-tom and cymbal channel 8.
+    pub fn set_mute_mask(&mut self, mask: u16) {
+        self.mute_mask = mask;
+    }
+
+    pub fn set_muted(&mut self, cycle: u64, muted: bool, output: &mut StereoBuffer) {
+        self.run_until(cycle, output);
+        self.muted = muted;
+        if muted {
+            self.update_output(cycle, 0, output);
+        }
+    }
+
+    pub fn write_address(&mut self, value: u8) {
+        self.address = value;
+    }
+
+    pub fn write_data(&mut self, cycle: u64, value: u8, output: &mut StereoBuffer) {
+        self.run_until(cycle, output);
+        let index = self.address as usize;
+        if index >= REGISTER_COUNT {
+            return;
+        }
+        let previous = self.registers[index];
+        self.registers[index] = value;
+        match index {
+            0x00..=0x07 | 0x30..=0x38 => self.update_patches(),
+            0x0E => {
+                self.update_patches();
+                self.write_rhythm(previous, value);
+            }
+            0x20..=0x28 => {
+                let channel = index - 0x20;
+                let rhythm = self.rhythm_mode() && channel >= RHYTHM_CHANNEL;
+                if !rhythm && (previous ^ value) & 0x10 != 0 {
+                    self.key(channel, value & 0x10 != 0, true, true);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn run_until(&mut self, cycle: u64, output: &mut StereoBuffer) {
+        while self.next_tick <= cycle {
+            let amplitude = (self.sample() * CHANNEL_AMPLITUDE) as i32;
+            self.update_output(self.next_tick, amplitude, output);
+            self.advance_tick();
+        }
+    }
+
+    // Each output sample takes 72 of the chip's clocks, carried over as a remainder when the
+    // cycles are counted at a different rate.
+    fn advance_tick(&mut self) {
+        match self.cycle_rate {
+            Some(rate) if rate != self.clock => {
+                let clock = self.clock as u64;
+                let step = self.tick_remainder + CLOCK_DIVIDER * rate as u64;
+                self.next_tick += step / clock;
+                self.tick_remainder = step % clock;
+            }
+            _ => self.next_tick += CLOCK_DIVIDER,
+        }
+    }
+
+    fn update_output(&mut self, cycle: u64, amplitude: i32, output: &mut StereoBuffer) {
+        let amplitude = if self.muted { 0 } else { amplitude };
+        let delta = amplitude - self.amplitude;
+        output.add_delta(cycle, delta, delta);
+        self.amplitude = amplitude;
+    }
+
+    fn write_rhythm(&mut self, previous: u8, value: u8) {
+        let changed = previous ^ value;
+        let keys = if value & RHYTHM_ENABLE != 0 { value } else { 0 };
+        let drums = [
+            (BASS_DRUM, RHYTHM_CHANNEL, true, true),
+            (HI_HAT, RHYTHM_CHANNEL + 1, true, false),
+            (SNARE_DRUM, RHYTHM_CHANNEL + 1, false, true),
+            (TOM_TOM, RHYTHM_CHANNEL + 2, true, false),
+            (CYMBAL, RHYTHM_CHANNEL + 2, false, true),
+        ];
+        for (bit, channel, modulator, carrier) in drums.iter() {
+            if changed & (bit | RHYTHM_ENABLE) != 0 {
+                self.key(*channel, keys & bit != 0, *modulator, *carrier);
+            }
+        }
+    }
+
+    fn key(&mut self, channel: usize, on: bool, modulator: bool, carrier: bool) {
+        if modulator {
+            Ym2413::switch(&mut self.modulators[channel], on);
+        }
+        if carrier {
+            Ym2413::switch(&mut self.carriers[channel], on);
+        }
+    }
+
+    fn switch(operator: &mut Operator, on: bool) {
+        if on {
+            operator.key_on();
+        } else {
+            operator.key_off();
+        }
+    }
+
+    // Patches are only rebuilt when the instrument, user instrument or rhythm registers change.
+    fn update_patches(&mut self) {
+        for channel in 0..CHANNEL_COUNT {
+            self.patches[channel] = self.patch(channel);
+        }
+    }
+
+    fn patch(&self, channel: usize) -> Patch {
+        let index = if self.rhythm_mode() && channel >= RHYTHM_CHANNEL {
+            BASS_DRUM_PATCH + channel - RHYTHM_CHANNEL
+        } else {
+            (self.registers[0x30 + channel] >> 4) as usize
+        };
+        if index == USER_PATCH {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&self.registers[0..8]);
+            Patch::from_bytes(&bytes)
+        } else {
+            Patch::from_bytes(&ROM[index])
+        }
+    }
+
+    fn frequency(&self, channel: usize) -> (u16, u8) {
+        let high = self.registers[0x20 + channel];
+        let fnum = self.registers[0x10 + channel] as u16 | ((high as u16 & 0x01) << 8);
+        (fnum, (high >> 1) & 0x07)
+    }
+
+    fn sample_rate(&self) -> f64 {
+        self.clock as f64 / CLOCK_DIVIDER as f64
+    }
+
+    fn sample(&mut self) -> f64 {
+        let sample_rate = self.sample_rate();
+        self.tremolo_phase = (self.tremolo_phase + TREMOLO_RATE / sample_rate).fract();
+        self.vibrato_phase = (self.vibrato_phase + VIBRATO_RATE / sample_rate).fract();
+        let tremolo = (1.0 - (2.0 * PI * self.tremolo_phase).cos()) / 2.0 * TREMOLO_DEPTH;
+        let vibrato = 2f64.powf((2.0 * PI * self.vibrato_phase).sin() * VIBRATO_CENTS / 1200.0);
+        if self.noise & 1 != 0 {
+            self.noise ^= NOISE_TAPS;
+        }
+        self.noise >>= 1;
+
+        let melodic = if self.rhythm_mode() {
+            RHYTHM_CHANNEL
+        } else {
+            CHANNEL_COUNT
+        };
+        let mut total = 0.0;
+        for channel in 0..melodic {
+            let output = self.melodic(channel, tremolo, vibrato);
+            if self.mute_mask & (1 << channel) == 0 {
+                total += output;
+            }
+        }
+        if self.rhythm_mode() {
+            total += self.rhythm(tremolo, vibrato);
+        }
+        total
+    }
+
+    fn advance(&mut self, channel: usize, patch: &Patch, vibrato: f64) {
+        let (fnum, block) = self.frequency(channel);
+        let sustain = self.registers[0x20 + channel] & 0x20 != 0;
+        let sample_rate = self.sample_rate();
+        let modulator = &mut self.modulators[channel];
+        modulator.advance_phase(&patch.modulator, fnum, block, vibrato);
+        modulator.advance_envelope(&patch.modulator, fnum, block, sustain, sample_rate);
+        let carrier = &mut self.carriers[channel];
+        carrier.advance_phase(&patch.carrier, fnum, block, vibrato);
+        carrier.advance_envelope(&patch.carrier, fnum, block, sustain, sample_rate);
+    }
+
+    fn melodic(&mut self, channel: usize, tremolo: f64, vibrato: f64) -> f64 {
+        let patch = self.patches[channel];
+        self.advance(channel, &patch, vibrato);
+        let (fnum, block) = self.frequency(channel);
+        let volume = (self.registers[0x30 + channel] & 0x0F) as f64 * 3.0;
+
+        let modulator = &mut self.modulators[channel];
+        let attenuation = modulator.attenuation(&patch.modulator, fnum, block, tremolo)
+            + patch.total_level as f64 * 0.75;
+        let feedback = modulator.feedback(patch.feedback);
+        let modulation = modulator.output(&patch.modulator, attenuation, feedback);
+
+        let carrier = &mut self.carriers[channel];
+        let attenuation = carrier.attenuation(&patch.carrier, fnum, block, tremolo) + volume;
+        carrier.output(&patch.carrier, attenuation, modulation * MODULATION_INDEX)
+    }
+
+    // The snare, hi-hat and cymbal are square waves built from phase bits of channels 7 and 8,
+    // mixed with the noise generator; all rhythm voices play at twice the melodic level.
+    fn rhythm(&mut self, tremolo: f64, vibrato: f64) -> f64 {
+        let bass_drum = self.melodic(RHYTHM_CHANNEL, tremolo, vibrato);
+
+        let snare_hi_hat = self.patches[RHYTHM_CHANNEL + 1];
+        let tom_cymbal = self.patches[RHYTHM_CHANNEL + 2];
+        self.advance(RHYTHM_CHANNEL + 1, &snare_hi_hat, vibrato);
+        self.advance(RHYTHM_CHANNEL + 2, &tom_cymbal, vibrato);
+
+        let noise = self.noise & 1 != 0;
+        let hi_hat_phase = (self.modulators[RHYTHM_CHANNEL + 1].phase * 1024.0) as u32;
+        let cymbal_phase = (self.carriers[RHYTHM_CHANNEL + 2].phase * 1024.0) as u32;
+        let ring = (((hi_hat_phase >> 2) ^ (hi_hat_phase >> 7))
+            | (hi_hat_phase >> 3)
+            | ((cymbal_phase >> 3) ^ (cymbal_phase >> 5)))
+            & 1
+            != 0;
+        let snare_high = self.carriers[RHYTHM_CHANNEL + 1].phase >= 0.5;
+
+        let level = |register: u8, high: bool| {
+            let nibble = if high { register >> 4 } else { register & 0x0F };
+            nibble as f64 * 3.0
+        };
+        let (fnum, block) = self.frequency(RHYTHM_CHANNEL + 1);
+        let volumes = self.registers[0x30 + RHYTHM_CHANNEL + 1];
+        let operator = &mut self.modulators[RHYTHM_CHANNEL + 1];
+        let attenuation = operator.attenuation(&snare_hi_hat.modulator, fnum, block, tremolo);
+        let hi_hat = operator.square(attenuation + level(volumes, true), ring != noise);
+        let operator = &mut self.carriers[RHYTHM_CHANNEL + 1];
+        let attenuation = operator.attenuation(&snare_hi_hat.carrier, fnum, block, tremolo);
+        let snare = operator.square(attenuation + level(volumes, false), snare_high != noise);
+
+        let (fnum, block) = self.frequency(RHYTHM_CHANNEL + 2);
+        let volumes = self.registers[0x30 + RHYTHM_CHANNEL + 2];
+        let operator = &mut self.modulators[RHYTHM_CHANNEL + 2];
+        let attenuation = operator.attenuation(&tom_cymbal.modulator, fnum, block, tremolo);
+        let tom = operator.output(
+            &tom_cymbal.modulator,
+            attenuation + level(volumes, true),
+            0.0,
+        );
+        let operator = &mut self.carriers[RHYTHM_CHANNEL + 2];
+        let attenuation = operator.attenuation(&tom_cymbal.carrier, fnum, block, tremolo);
+        let cymbal = operator.square(attenuation + level(volumes, false), ring);
+
+        let audible = |channel: usize, output: f64| {
+            if self.mute_mask & (1 << channel) == 0 {
+                output
+            } else {
+                0.0
+            }
+        };
+        2.0 * (audible(RHYTHM_CHANNEL, bass_drum)
+            + audible(RHYTHM_CHANNEL + 1, hi_hat + snare)
+            + audible(RHYTHM_CHANNEL + 2, tom + cymbal))
+    }
+}
@@ -0,0 +1,192 @@
+use std::f64::consts::PI;
+
+use crate::vm::ym2413::patches::OperatorPatch;
+
+pub const MAX_ATTENUATION: f64 = 96.0;
+
+const MULTIPLES: [f64; 16] = [
+    0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 10.0, 12.0, 12.0, 15.0, 15.0,
+];
+const KEY_SCALE_LEVELS: [f64; 16] = [
+    0.0, 18.0, 24.0, 27.75, 30.0, 32.25, 33.75, 35.25, 36.0, 37.5, 38.25, 39.0, 39.75, 40.5, 41.25,
+    42.0,
+];
+const KEY_SCALE_SHIFTS: [f64; 4] = [0.0, 0.25, 0.5, 1.0];
+const ATTACK_TIME: f64 = 2.826;
+const DECAY_TIME: f64 = 39.28;
+const SUSTAIN_RELEASE_RATE: u8 = 5;
+const PERCUSSIVE_RELEASE_RATE: u8 = 7;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EnvelopeState {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+    Off,
+}
+
+#[derive(Copy, Clone)]
+pub struct Operator {
+    pub phase: f64,
+    envelope: f64,
+    state: EnvelopeState,
+    output: f64,
+    previous: f64,
+}
+
+impl Operator {
+    pub fn new() -> Operator {
+        Operator {
+            phase: 0.0,
+            envelope: MAX_ATTENUATION,
+            state: EnvelopeState::Off,
+            output: 0.0,
+            previous: 0.0,
+        }
+    }
+
+    pub fn state(&self) -> EnvelopeState {
+        self.state
+    }
+
+    pub fn key_on(&mut self) {
+        self.phase = 0.0;
+        self.state = EnvelopeState::Attack;
+    }
+
+    pub fn key_off(&mut self) {
+        if self.state != EnvelopeState::Off {
+            self.state = EnvelopeState::Release;
+        }
+    }
+
+    pub fn advance_phase(&mut self, patch: &OperatorPatch, fnum: u16, block: u8, vibrato: f64) {
+        let mut step =
+            (fnum as f64) * 2f64.powi(block as i32 - 19) * MULTIPLES[patch.multiple as usize];
+        if patch.vibrato {
+            step *= vibrato;
+        }
+        self.phase = (self.phase + step).fract();
+    }
+
+    pub fn advance_envelope(
+        &mut self,
+        patch: &OperatorPatch,
+        fnum: u16,
+        block: u8,
+        sustain: bool,
+        sample_rate: f64,
+    ) {
+        let key_scale = ((block << 1) as u16 | (fnum >> 8)) as u8;
+        let key_scale = if patch.key_scale_rate {
+            key_scale
+        } else {
+            key_scale >> 2
+        };
+        let rate = |r: u8| {
+            if r == 0 {
+                0
+            } else {
+                (r * 4 + key_scale).min(63)
+            }
+        };
+        let decay_step = |r: u8| {
+            if r == 0 {
+                0.0
+            } else {
+                MAX_ATTENUATION / (DECAY_TIME * 2f64.powf(-(r as f64 - 4.0) / 4.0) * sample_rate)
+            }
+        };
+
+        match self.state {
+            EnvelopeState::Attack => {
+                let r = rate(patch.attack);
+                if r >= 60 {
+                    self.envelope = 0.0;
+                } else if r > 0 {
+                    let time = ATTACK_TIME * 2f64.powf(-(r as f64 - 4.0) / 4.0);
+                    self.envelope -= (self.envelope + 4.0) * 8.0 / (time * sample_rate);
+                }
+                if self.envelope <= 0.0 {
+                    self.envelope = 0.0;
+                    self.state = EnvelopeState::Decay;
+                }
+            }
+            EnvelopeState::Decay => {
+                self.envelope += decay_step(rate(patch.decay));
+                let level = patch.sustain_level as f64 * 3.0;
+                if self.envelope >= level {
+                    self.envelope = level;
+                    self.state = EnvelopeState::Sustain;
+                }
+            }
+            EnvelopeState::Sustain => {
+                if !patch.sustained {
+                    self.envelope += decay_step(rate(patch.release));
+                }
+            }
+            EnvelopeState::Release => {
+                let release = if sustain {
+                    SUSTAIN_RELEASE_RATE
+                } else if patch.sustained {
+                    patch.release
+                } else {
+                    PERCUSSIVE_RELEASE_RATE
+                };
+                self.envelope += decay_step(rate(release));
+            }
+            EnvelopeState::Off => {}
+        }
+        if self.envelope >= MAX_ATTENUATION {
+            self.envelope = MAX_ATTENUATION;
+            if self.state != EnvelopeState::Attack {
+                self.state = EnvelopeState::Off;
+            }
+        }
+    }
+
+    pub fn attenuation(&self, patch: &OperatorPatch, fnum: u16, block: u8, tremolo: f64) -> f64 {
+        let octave = KEY_SCALE_LEVELS[(fnum >> 5) as usize] - 6.0 * (7 - block) as f64;
+        let key_scale = octave.max(0.0) * KEY_SCALE_SHIFTS[patch.key_scale_level as usize];
+        let tremolo = if patch.tremolo { tremolo } else { 0.0 };
+        self.envelope + key_scale + tremolo
+    }
+
+    pub fn output(&mut self, patch: &OperatorPatch, attenuation: f64, modulation: f64) -> f64 {
+        let amplitude = if attenuation >= MAX_ATTENUATION {
+            0.0
+        } else {
+            10f64.powf(-attenuation / 20.0)
+        };
+        let wave = (2.0 * PI * self.phase + modulation).sin();
+        let wave = if patch.rectified && wave < 0.0 {
+            0.0
+        } else {
+            wave
+        };
+        self.previous = self.output;
+        self.output = amplitude * wave;
+        self.output
+    }
+
+    pub fn square(&mut self, attenuation: f64, high: bool) -> f64 {
+        let amplitude = if attenuation >= MAX_ATTENUATION {
+            0.0
+        } else {
+            10f64.powf(-attenuation / 20.0)
+        };
+        self.previous = self.output;
+        self.output = if high { amplitude } else { -amplitude };
+        self.output
+    }
+
+    // Feedback modulates by the mean of the two previous outputs, up to 4 pi at level 7.
+    pub fn feedback(&self, level: u8) -> f64 {
+        if level == 0 {
+            0.0
+        } else {
+            (self.output + self.previous) / 2.0 * 4.0 * PI / 2f64.powi(7 - level as i32)
+        }
+    }
+}
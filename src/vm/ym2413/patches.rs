@@ -0,0 +1,73 @@
+pub const USER_PATCH: usize = 0;
+pub const BASS_DRUM_PATCH: usize = 16;
+pub const SNARE_HI_HAT_PATCH: usize = 17;
+pub const TOM_CYMBAL_PATCH: usize = 18;
+
+pub const ROM: [[u8; 8]; 19] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x61, 0x61, 0x1E, 0x17, 0xF0, 0x7F, 0x00, 0x17],
+    [0x13, 0x41, 0x16, 0x0E, 0xFD, 0xF4, 0x23, 0x23],
+    [0x03, 0x01, 0x9A, 0x04, 0xF3, 0xF3, 0x13, 0xF3],
+    [0x11, 0x61, 0x0E, 0x07, 0xFA, 0x64, 0x70, 0x17],
+    [0x22, 0x21, 0x1E, 0x06, 0xF0, 0x76, 0x00, 0x28],
+    [0x21, 0x22, 0x16, 0x05, 0xF0, 0x71, 0x00, 0x18],
+    [0x21, 0x61, 0x1D, 0x07, 0x82, 0x80, 0x17, 0x17],
+    [0x23, 0x21, 0x2D, 0x16, 0x90, 0x90, 0x00, 0x07],
+    [0x21, 0x21, 0x1B, 0x06, 0x64, 0x65, 0x10, 0x17],
+    [0x21, 0x21, 0x0B, 0x1A, 0x85, 0xA0, 0x70, 0x07],
+    [0x23, 0x01, 0x83, 0x10, 0xFF, 0xB4, 0x10, 0xF4],
+    [0x97, 0xC1, 0x20, 0x07, 0xFF, 0xF4, 0x22, 0x22],
+    [0x61, 0x00, 0x0C, 0x05, 0xC2, 0xF6, 0x40, 0x44],
+    [0x01, 0x01, 0x56, 0x03, 0x94, 0xC2, 0x03, 0x12],
+    [0x21, 0x01, 0x89, 0x03, 0xF1, 0xE4, 0xF0, 0x23],
+    [0x07, 0x21, 0x14, 0x00, 0xEE, 0xF8, 0xFF, 0xF8],
+    [0x01, 0x31, 0x00, 0x00, 0xF8, 0xF7, 0xF8, 0xF7],
+    [0x25, 0x11, 0x00, 0x00, 0xF8, 0xFA, 0xF8, 0x55],
+];
+
+#[derive(Copy, Clone)]
+pub struct OperatorPatch {
+    pub tremolo: bool,
+    pub vibrato: bool,
+    pub sustained: bool,
+    pub key_scale_rate: bool,
+    pub multiple: u8,
+    pub key_scale_level: u8,
+    pub rectified: bool,
+    pub attack: u8,
+    pub decay: u8,
+    pub sustain_level: u8,
+    pub release: u8,
+}
+
+#[derive(Copy, Clone)]
+pub struct Patch {
+    pub modulator: OperatorPatch,
+    pub carrier: OperatorPatch,
+    pub total_level: u8,
+    pub feedback: u8,
+}
+
+impl Patch {
+    pub fn from_bytes(bytes: &[u8; 8]) -> Patch {
+        let operator = |index: usize, rectified: bool| OperatorPatch {
+            tremolo: bytes[index] & 0x80 != 0,
+            vibrato: bytes[index] & 0x40 != 0,
+            sustained: bytes[index] & 0x20 != 0,
+            key_scale_rate: bytes[index] & 0x10 != 0,
+            multiple: bytes[index] & 0x0F,
+            key_scale_level: bytes[2 + index] >> 6,
+            rectified,
+            attack: bytes[4 + index] >> 4,
+            decay: bytes[4 + index] & 0x0F,
+            sustain_level: bytes[6 + index] >> 4,
+            release: bytes[6 + index] & 0x0F,
+        };
+        Patch {
+            modulator: operator(0, bytes[3] & 0x08 != 0),
+            carrier: operator(1, bytes[3] & 0x10 != 0),
+            total_level: bytes[2] & 0x3F,
+            feedback: bytes[3] & 0x07,
+        }
+    }
+}
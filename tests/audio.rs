@@ -0,0 +1,161 @@
+extern crate rusty_sms;
+
+use std::cell::RefCell;
+use std::io::{self, Cursor};
+use std::rc::Rc;
+
+use rusty_sms::vm::audio::blip_buffer::BlipBuffer;
+use rusty_sms::vm::audio::pcm::PcmWriter;
+use rusty_sms::vm::audio::sink::AudioSink;
+use rusty_sms::vm::audio::wav::WavWriter;
+use rusty_sms::vm::machine::Machine;
+
+const CLOCK: u32 = 3_579_545;
+
+#[test]
+fn sample_count() {
+    let mut buffer = BlipBuffer::new(CLOCK, 44_100);
+    buffer.end_frame(CLOCK as u64 / 3);
+    let first = buffer.take_samples().len();
+    buffer.end_frame(CLOCK as u64);
+    assert_eq!(44_100, first + buffer.take_samples().len());
+
+    buffer.set_sample_rate(48_000);
+    buffer.end_frame(CLOCK as u64 * 2);
+    assert_eq!(48_000, buffer.take_samples().len());
+}
+
+#[test]
+fn step() {
+    let mut buffer = BlipBuffer::new(CLOCK, 44_100);
+    buffer.set_high_pass(None);
+    buffer.add_delta(1000, 10_000);
+    buffer.end_frame(CLOCK as u64 / 100);
+
+    let samples = buffer.take_samples();
+    assert_eq!(0, samples[0]);
+    assert!(samples[30..].iter().all(|s| *s == 10_000));
+    assert!(samples.iter().all(|s| *s <= 11_500 && *s >= -1_500));
+}
+
+#[test]
+fn rate_change_mid_tone() {
+    let mut buffer = BlipBuffer::new(CLOCK, 44_100);
+    buffer.set_high_pass(None);
+    let end = CLOCK as u64 / 100;
+    // A square wave between 0 and 10,000 that rises just before the frame ends, leaving most
+    // of the step in the buffer when the rate changes.
+    for edge in 0..30 {
+        let delta = if edge % 2 == 0 { 10_000 } else { -10_000 };
+        buffer.add_delta(edge * 1_000, delta);
+    }
+    buffer.add_delta(end - 10, 10_000);
+    buffer.end_frame(end);
+    buffer.take_samples();
+
+    buffer.set_clock_rate(CLOCK / 2);
+    buffer.add_delta(end + 100, -10_000);
+    buffer.end_frame(end + CLOCK as u64 / 200);
+    let samples = buffer.take_samples();
+    assert!(samples[samples.len() - 100..].iter().all(|s| *s == 0));
+}
+
+#[test]
+fn high_pass() {
+    let mut buffer = BlipBuffer::new(CLOCK, 44_100);
+    buffer.add_delta(0, 10_000);
+    buffer.end_frame(CLOCK as u64);
+
+    let samples = buffer.take_samples();
+    assert!(samples[20] > 9_000);
+    assert!(samples[44_000].abs() < 10);
+}
+
+#[test]
+fn low_pass() {
+    let mut buffer = BlipBuffer::new(CLOCK, 44_100);
+    buffer.set_high_pass(None);
+    buffer.set_low_pass(Some(1_000.0));
+    buffer.add_delta(0, 10_000);
+    buffer.end_frame(CLOCK as u64 / 100);
+
+    let samples = buffer.take_samples();
+    assert!(samples[10] < 9_000);
+    assert!(samples[400] > 9_990);
+}
+
+#[test]
+fn band_limited() {
+    let mut buffer = BlipBuffer::new(CLOCK, 44_100);
+    buffer.set_high_pass(None);
+    let mut level = 1_000;
+    for cycle in (0..CLOCK as u64 / 10).step_by(16) {
+        buffer.add_delta(cycle, level * 2);
+        level = -level;
+    }
+    buffer.end_frame(CLOCK as u64 / 10);
+
+    let samples = buffer.take_samples();
+    assert!(samples[100..]
+        .iter()
+        .all(|s| (*s as i32 - 1_000).abs() < 100));
+}
+
+#[test]
+fn wav_writer() {
+    let mut writer = WavWriter::new(Cursor::new(Vec::new()), 2, 48_000).unwrap();
+    writer.write_samples(&[1, -1, 2, -2]);
+    assert_eq!(2, writer.frames_written());
+    let file = writer.into_inner().unwrap().into_inner();
+
+    assert_eq!(52, file.len());
+    assert_eq!(b"RIFF", &file[0..4]);
+    assert_eq!(&44u32.to_le_bytes(), &file[4..8]);
+    assert_eq!(&2u16.to_le_bytes(), &file[22..24]);
+    assert_eq!(&48_000u32.to_le_bytes(), &file[24..28]);
+    assert_eq!(&8u32.to_le_bytes(), &file[40..44]);
+    assert_eq!(&[0x01, 0x00, 0xFF, 0xFF], &file[44..48]);
+}
+
+#[test]
+fn pcm_writer_mono() {
+    let mut writer = PcmWriter::new(Vec::new(), 1, 44_100);
+    writer.write_samples(&[100, 300, -50, -150]);
+    writer.finish().unwrap();
+    let bytes = writer.into_inner().unwrap();
+    assert_eq!(vec![200, 0, 0x9C, 0xFF], bytes);
+}
+
+struct Recorder {
+    samples: Rc<RefCell<Vec<i16>>>,
+}
+
+impl AudioSink for Recorder {
+    fn sample_rate(&self) -> u32 {
+        22_050
+    }
+
+    fn write_samples(&mut self, samples: &[i16]) {
+        self.samples.borrow_mut().extend_from_slice(samples);
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn machine_sink() {
+    let samples = Rc::new(RefCell::new(Vec::new()));
+    let mut vm = Machine::new();
+    vm.set_audio_sink(Box::new(Recorder {
+        samples: samples.clone(),
+    }));
+    assert_eq!(22_050, vm.sample_rate());
+
+    let frame = vm.run_frame();
+    assert_eq!(frame.audio, *samples.borrow());
+    assert!(vm.take_audio_sink().is_some());
+    vm.run_frame();
+    assert_eq!(frame.audio.len(), samples.borrow().len());
+}
@@ -0,0 +1,101 @@
+extern crate rusty_sms;
+
+use rusty_sms::vm::builder::{MachineBuilder, Preset};
+use rusty_sms::vm::io::peripheral::Peripheral;
+use rusty_sms::vm::io::Region;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::ram::MemoryMap;
+use rusty_sms::vm::system::System;
+use rusty_sms::vm::vdp::{TvSystem, VdpModel};
+
+#[test]
+fn presets() {
+    let mark3 = MachineBuilder::preset(Preset::MarkIII).build();
+    assert_eq!(VdpModel::Sms1, mark3.vdp.model());
+    assert_eq!(Region::Japan, mark3.region());
+    assert!(!mark3.has_fm_unit());
+
+    let japanese = MachineBuilder::preset(Preset::SmsJapan).build();
+    assert_eq!(VdpModel::Sms1, japanese.vdp.model());
+    assert_eq!(Region::Japan, japanese.region());
+    assert!(japanese.has_fm_unit());
+
+    let sms2 = MachineBuilder::new().build();
+    assert_eq!(VdpModel::Sms2, sms2.vdp.model());
+    assert_eq!(MemoryMap::with_ram_size(0x2000), sms2.ram.map());
+
+    let game_gear = MachineBuilder::preset(Preset::GameGear).build();
+    assert_eq!(VdpModel::GameGear, game_gear.vdp.model());
+
+    let sg1000 = MachineBuilder::preset(Preset::Sg1000).build();
+    assert_eq!(System::Sg1000, sg1000.system());
+    assert_eq!(MemoryMap::SG1000, sg1000.ram.map());
+
+    let mut z80 = MachineBuilder::preset(Preset::GenericZ80).build();
+    assert_eq!(MemoryMap::Flat, z80.ram.map());
+    assert_eq!(0xFF, z80.read_port(0xBF));
+}
+
+#[test]
+fn overrides() {
+    let vm = MachineBuilder::preset(Preset::Sms1)
+        .vdp_model(VdpModel::Sms2)
+        .region(Region::ExportPal)
+        .fm_unit(true)
+        .ram_size(0x8000)
+        .peripheral(1, Peripheral::Paddle)
+        .sample_rate(48_000)
+        .build();
+    assert_eq!(VdpModel::Sms2, vm.vdp.model());
+    assert_eq!(TvSystem::Pal, vm.tv_system());
+    assert!(vm.has_fm_unit());
+    assert_eq!(
+        MemoryMap::Cartridge {
+            rom_end: 0x8000,
+            ram_mask: 0x7FFF
+        },
+        vm.ram.map()
+    );
+    assert_eq!(Peripheral::Paddle, vm.peripheral(1));
+    assert_eq!(48_000, vm.sample_rate());
+}
+
+#[test]
+fn system_after_preset() {
+    let sg1000 = MachineBuilder::new().system(System::Sg1000).build();
+    assert_eq!(VdpModel::Tms9918a, sg1000.vdp.model());
+    assert_eq!(MemoryMap::SG1000, sg1000.ram.map());
+
+    let master_gear = MachineBuilder::preset(Preset::Sms1)
+        .system(System::MasterGear)
+        .build();
+    assert_eq!(VdpModel::GameGear, master_gear.vdp.model());
+    assert!(master_gear.vdp.sms_mode());
+    assert_eq!(MemoryMap::Flat, master_gear.ram.map());
+
+    let sc3000 = MachineBuilder::preset(Preset::GameGear)
+        .system(System::Sc3000)
+        .ram_size(0x8000)
+        .build();
+    assert_eq!(VdpModel::Tms9918a, sc3000.vdp.model());
+    assert_eq!(MemoryMap::SC3000_BASIC, sc3000.ram.map());
+}
+
+#[test]
+fn bios_and_cartridge() {
+    let mut vm = MachineBuilder::new()
+        .bios(&[0xB1, 0x05])
+        .cartridge(&[0xCA])
+        .build();
+    assert_eq!(0xE3, vm.memory_control());
+    assert_eq!(0xB1, vm.ram.read_u8(0x0000));
+
+    vm.write_port(0x3E, 0xAB);
+    assert_eq!(0xCA, vm.ram.read_u8(0x0000));
+    assert_eq!(0xFF, vm.ram.read_u8(0x0001));
+
+    let mut plain: Machine = MachineBuilder::new().cartridge(&[0xCA]).build();
+    assert_eq!(0xCA, plain.ram.read_u8(0x0000));
+    plain.ram.write_u8(0x0000, 0x00);
+    assert_eq!(0xCA, plain.ram.read_u8(0x0000));
+}
@@ -0,0 +1,151 @@
+extern crate rusty_sms;
+
+use rusty_sms::vm::io::Region;
+use rusty_sms::vm::machine::Machine;
+
+const SINE_PATCH: [u8; 8] = [0x01, 0x21, 0x3F, 0x00, 0xF0, 0xF0, 0x0F, 0x0F];
+
+fn write_register(vm: &mut Machine, register: u8, value: u8) {
+    vm.write_port(0xF0, register);
+    vm.write_port(0xF1, value);
+}
+
+fn play_sine(vm: &mut Machine) {
+    for (register, value) in SINE_PATCH.iter().enumerate() {
+        write_register(vm, register as u8, *value);
+    }
+    write_register(vm, 0x30, 0x00);
+    write_register(vm, 0x10, 0x22);
+    write_register(vm, 0x20, 0x19);
+}
+
+fn run_frames(vm: &mut Machine, frames: usize) -> Vec<i16> {
+    (0..frames).flat_map(|_| vm.run_frame().audio).collect()
+}
+
+fn rising_edges(samples: &[i16]) -> usize {
+    let mut high = true;
+    let mut edges = 0;
+    for sample in samples {
+        if high && *sample < -1000 {
+            high = false;
+        } else if !high && *sample > 1000 {
+            high = true;
+            edges += 1;
+        }
+    }
+    edges
+}
+
+#[test]
+fn detection() {
+    let mut vm = Machine::new();
+    vm.write_port(0xF2, 0x01);
+    assert_ne!(0x01, vm.read_port(0xF2) & 0x03);
+
+    vm.set_fm_unit(true);
+    for value in 0..4 {
+        vm.write_port(0xF2, value);
+        assert_eq!(value, vm.read_port(0xF2));
+    }
+}
+
+#[test]
+fn registers() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    write_register(&mut vm, 0x35, 0x3A);
+    write_register(&mut vm, 0x0E, 0x20);
+    let fm = vm.fm.as_ref().unwrap();
+    assert_eq!(0x3A, fm.register(0x35));
+    assert!(fm.rhythm_mode());
+}
+
+#[test]
+fn tone() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.write_port(0xF2, 0x01);
+    play_sine(&mut vm);
+    assert!(vm.fm.as_ref().unwrap().is_sounding(0));
+
+    let samples = run_frames(&mut vm, 60);
+    let edges = rising_edges(&samples);
+    assert!((435..=445).contains(&edges), "{} edges.", edges);
+}
+
+#[test]
+fn user_patch_change() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.write_port(0xF2, 0x01);
+    play_sine(&mut vm);
+    run_frames(&mut vm, 1);
+    write_register(&mut vm, 0x01, 0x22);
+
+    let samples = run_frames(&mut vm, 60);
+    let edges = rising_edges(&samples);
+    assert!((875..=885).contains(&edges), "{} edges.", edges);
+}
+
+#[test]
+fn pal_clock() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.set_region(Region::ExportPal);
+    assert_eq!(3_546_893, vm.fm.as_ref().unwrap().clock());
+
+    vm.set_fm_unit(false);
+    vm.set_fm_unit(true);
+    assert_eq!(3_546_893, vm.fm.as_ref().unwrap().clock());
+}
+
+#[test]
+fn key_off() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.write_port(0xF2, 0x01);
+    play_sine(&mut vm);
+    run_frames(&mut vm, 2);
+    write_register(&mut vm, 0x20, 0x09);
+    run_frames(&mut vm, 2);
+    assert!(!vm.fm.as_ref().unwrap().is_sounding(0));
+}
+
+#[test]
+fn audio_control() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    play_sine(&mut vm);
+    run_frames(&mut vm, 2);
+    let samples = run_frames(&mut vm, 2);
+    assert!(samples.iter().all(|s| s.abs() < 64));
+
+    vm.write_port(0xF2, 0x03);
+    let samples = run_frames(&mut vm, 2);
+    assert!(rising_edges(&samples) > 0);
+}
+
+#[test]
+fn rhythm() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.write_port(0xF2, 0x01);
+    write_register(&mut vm, 0x16, 0x20);
+    write_register(&mut vm, 0x26, 0x05);
+    write_register(&mut vm, 0x36, 0x00);
+    write_register(&mut vm, 0x0E, 0x30);
+    let samples = run_frames(&mut vm, 4);
+    assert!(samples.iter().any(|s| s.abs() > 1000));
+}
+
+#[test]
+fn mute_mask() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.write_port(0xF2, 0x01);
+    vm.fm.as_mut().unwrap().set_mute_mask(0x0001);
+    play_sine(&mut vm);
+    let samples = run_frames(&mut vm, 2);
+    assert!(samples.iter().all(|s| s.abs() < 64));
+}
@@ -0,0 +1,312 @@
+extern crate rusty_sms;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rusty_sms::element::Element::Instruction;
+use rusty_sms::program::Program;
+use rusty_sms::vm::callbacks::Callbacks;
+use rusty_sms::vm::cpu::registers::Registers;
+use rusty_sms::vm::instructions::mnemonics::Mnemonic;
+use rusty_sms::vm::io::joypad::Joypad;
+use rusty_sms::vm::io::light_phaser::LightPhaser;
+use rusty_sms::vm::io::paddle::Paddle;
+use rusty_sms::vm::io::peripheral::Peripheral;
+use rusty_sms::vm::io::sports_pad::SportsPad;
+use rusty_sms::vm::io::Region;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::vdp::VdpModel;
+
+#[test]
+fn released() {
+    let mut vm = Machine::new();
+    assert_eq!(0xFF, vm.read_port(0xDC));
+    assert_eq!(0xFF, vm.read_port(0xDD));
+}
+
+#[test]
+fn player1() {
+    let mut vm = Machine::new();
+    let pad = Joypad {
+        up: true,
+        right: true,
+        button2: true,
+        ..Joypad::new()
+    };
+    vm.set_joypad(0, pad);
+    assert_eq!(pad, vm.joypad(0));
+    assert_eq!(0xD6, vm.read_port(0xDC));
+    assert_eq!(0xFF, vm.read_port(0xDD));
+}
+
+#[test]
+fn player2() {
+    let mut vm = Machine::new();
+    let pad = Joypad {
+        down: true,
+        left: true,
+        button1: true,
+        ..Joypad::new()
+    };
+    vm.set_joypads(Joypad::new(), pad);
+    assert_eq!(0x7F, vm.read_port(0xDC));
+    assert_eq!(0xFA, vm.read_port(0xDD));
+}
+
+#[test]
+fn reset_button() {
+    let mut vm = Machine::new();
+    vm.set_reset_button(true);
+    assert_eq!(0xEF, vm.read_port(0xDD));
+    assert_eq!(0xEF, vm.read_port(0xC1));
+}
+
+#[test]
+fn read_from_program() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    p.add_param(Mnemonic::InAVX, 0xDC);
+    vm.load(&p);
+    vm.set_joypad(
+        0,
+        Joypad {
+            button1: true,
+            ..Joypad::new()
+        },
+    );
+    vm.run_frame();
+    assert_eq!(0xEF, vm.get_register(Registers::a()));
+}
+
+#[test]
+fn pause_nmi() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    p.add(Instruction(Mnemonic::Halt));
+    vm.load(&p);
+    vm.set_register_pair(|s| &mut s.sp, 0xDFF0);
+    vm.run_frame();
+    assert!(vm.cpu.is_halted());
+
+    vm.set_pause_button(true);
+    assert!(vm.nmi_pending());
+    vm.run_frame();
+    assert!(!vm.nmi_pending());
+    assert!(!vm.cpu.is_halted());
+    assert_eq!(0x0001, vm.ram.read_u16(0xDFEE));
+
+    vm.set_pause_button(true);
+    assert!(!vm.nmi_pending());
+    vm.set_pause_button(false);
+    vm.press_pause();
+    assert!(vm.nmi_pending());
+}
+
+#[test]
+fn game_gear_ports() {
+    let mut vm = Machine::with_vdp_model(VdpModel::GameGear);
+    vm.press_pause();
+    assert!(!vm.nmi_pending());
+
+    assert_eq!(0xC0, vm.read_port(0x00));
+    vm.set_start_button(true);
+    assert_eq!(0x40, vm.read_port(0x00));
+    vm.set_region(Region::Japan);
+    assert_eq!(0x00, vm.read_port(0x00));
+    vm.set_region(Region::ExportPal);
+    vm.set_start_button(false);
+    assert_eq!(0xE0, vm.read_port(0x00));
+
+    assert_eq!(0x7F, vm.read_port(0x01));
+    vm.write_port(0x02, 0x00);
+    vm.write_port(0x01, 0x12);
+    assert_eq!(0x12, vm.read_port(0x01));
+    vm.write_port(0x00, 0x00);
+    assert_eq!(0xE0, vm.read_port(0x00));
+}
+
+// Returns each line on which TH was seen low, whether it was seen high on that line before it
+// fell, and the latched H counter.
+fn phaser_hits(backdrop: u8) -> (Vec<u8>, bool, u8) {
+    let mut vm = Machine::new();
+    for (register, value) in [(0, 0x04), (1, 0x40), (2, 0xFF), (5, 0xFF)].iter() {
+        vm.vdp.write_control(*value);
+        vm.vdp.write_control(0x80 | register);
+    }
+    vm.vdp.write_control(0x00);
+    vm.vdp.write_control(0x7F);
+    vm.vdp.write_data(0xD0);
+    vm.vdp.write_control(0x00);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x00);
+
+    vm.set_peripheral(0, Peripheral::LightPhaser);
+    vm.set_light_phaser(
+        0,
+        LightPhaser {
+            x: 100,
+            y: 50,
+            trigger: true,
+        },
+    );
+    vm.run_frame();
+    assert_eq!(0x2F, vm.read_port(0xDC) & 0x3F);
+    assert_eq!(0x40, vm.read_port(0xDD) & 0x40);
+
+    // The screen flashes in the frame that is read, after a dark one.
+    vm.vdp.write_control(0x00);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(backdrop);
+    let samples = Rc::new(RefCell::new(Vec::new()));
+    let recorder = samples.clone();
+    let mut callbacks = Callbacks::new();
+    callbacks.on_before_instruction_fetch(Box::new(move |m| {
+        let low = m.read_port(0xDD) & 0x40 == 0;
+        let sample = (m.read_port(0x7E), low);
+        let mut samples = recorder.borrow_mut();
+        if samples.last() != Some(&sample) {
+            samples.push(sample);
+        }
+    }));
+    vm.run_frame_with(&mut callbacks);
+    let samples = samples.borrow();
+    let lines: Vec<u8> = samples
+        .iter()
+        .filter(|(_, low)| *low)
+        .map(|(line, _)| *line)
+        .collect();
+    let high_first = lines.iter().all(|line| samples.contains(&(*line, false)));
+    (lines, high_first, vm.read_port(0x7F))
+}
+
+#[test]
+fn light_phaser() {
+    let (lines, high_first, h_counter) = phaser_hits(0x3F);
+    assert_eq!((46..=54).collect::<Vec<u8>>(), lines);
+    assert!(high_first);
+    assert_eq!(96 / 2 + 0x14, h_counter);
+
+    let (lines, _, _) = phaser_hits(0x00);
+    assert!(lines.is_empty());
+}
+
+#[test]
+fn paddle_export() {
+    let mut vm = Machine::new();
+    vm.set_peripheral(0, Peripheral::Paddle);
+    vm.set_paddle(
+        0,
+        Paddle {
+            position: 0xA5,
+            button: true,
+        },
+    );
+    vm.write_port(0x3F, 0xFD);
+    assert_eq!(0x05, vm.read_port(0xDC) & 0x3F);
+    vm.write_port(0x3F, 0xDD);
+    assert_eq!(0x2A, vm.read_port(0xDC) & 0x3F);
+}
+
+#[test]
+fn paddle_japanese() {
+    let mut vm = Machine::new();
+    vm.set_region(Region::Japan);
+    vm.set_peripheral(1, Peripheral::Paddle);
+    vm.set_paddle(
+        1,
+        Paddle {
+            position: 0x3C,
+            button: false,
+        },
+    );
+
+    let reads = Rc::new(RefCell::new(Vec::new()));
+    let recorder = reads.clone();
+    let mut callbacks = Callbacks::new();
+    callbacks.on_before_instruction_fetch(Box::new(move |m| {
+        let lines = (m.read_port(0xDC) >> 6) | ((m.read_port(0xDD) & 0x0F) << 2);
+        recorder.borrow_mut().push(lines);
+    }));
+    vm.run_frame_with(&mut callbacks);
+    let reads = reads.borrow();
+    assert!(reads.contains(&0x1C));
+    assert!(reads.contains(&0x33));
+    assert!(reads.iter().all(|lines| *lines == 0x1C || *lines == 0x33));
+}
+
+#[test]
+fn sports_pad() {
+    let mut vm = Machine::new();
+    vm.set_peripheral(0, Peripheral::SportsPad);
+    vm.set_sports_pad(
+        0,
+        SportsPad {
+            x: 0x12,
+            y: -3,
+            button1: true,
+            button2: false,
+        },
+    );
+    vm.run_frame();
+    assert_eq!(0xE1, vm.read_port(0xDC));
+
+    let mut nibbles = Vec::new();
+    for value in [0xFD, 0xDD, 0xFD, 0xDD, 0xFD, 0xDD, 0xFD].iter() {
+        vm.write_port(0x3F, *value);
+        nibbles.push(vm.read_port(0xDC));
+    }
+    assert_eq!(vec![0xE1, 0xE2, 0xEF, 0xED, 0xE1, 0xE2, 0xEF], nibbles);
+
+    vm.run_frame();
+    assert_eq!(0xE1, vm.read_port(0xDC));
+    vm.write_port(0x3F, 0xDD);
+    assert_eq!(0xE2, vm.read_port(0xDC));
+}
+
+fn nationality(region: Region) -> (u8, u8) {
+    let mut vm = Machine::new();
+    vm.set_region(region);
+    vm.write_port(0x3F, 0xF5);
+    let high = vm.read_port(0xDD) & 0xC0;
+    vm.write_port(0x3F, 0x55);
+    let low = vm.read_port(0xDD) & 0xC0;
+    (high, low)
+}
+
+#[test]
+fn region_detection() {
+    assert_eq!((0xC0, 0x00), nationality(Region::ExportNtsc));
+    assert_eq!((0xC0, 0x00), nationality(Region::ExportPal));
+    assert_eq!((0x00, 0xC0), nationality(Region::Japan));
+}
+
+#[test]
+fn tr_outputs() {
+    let mut vm = Machine::new();
+    vm.write_port(0x3F, 0xAA);
+    assert_eq!(0xDF, vm.read_port(0xDC));
+    assert_eq!(0xF7, vm.read_port(0xDD));
+
+    vm.write_port(0x3F, 0xFF);
+    assert_eq!(0xFF, vm.read_port(0xDC));
+    assert_eq!(0xFF, vm.read_port(0xDD));
+}
+
+#[test]
+fn th_latches_h_counter() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    for value in [0xFD, 0xDD, 0xFD].iter() {
+        p.add_param(Mnemonic::LdAX, *value);
+        p.add_param(Mnemonic::OutVXA, 0x3F);
+    }
+    p.add(Instruction(Mnemonic::Halt));
+    vm.load(&p);
+    vm.run_frame();
+    assert_eq!(0xFD, vm.io_control());
+    // Only the fall at the second write latches; the rise after it leaves the counter alone.
+    assert_eq!(
+        (7 + 11 + 7 + 11) * 3 / 2 / 2 + 0x14,
+        vm.read_port(0x7F) as u64
+    );
+}
@@ -134,6 +134,90 @@ fn jump() {
     jump_test_dual(Mnemonic::JpMXX, Flag::Sign, true);
 }
 
+#[test]
+fn call_pushes_return_address() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    p.add_param_word(Mnemonic::CallXX, 0x0123);
+    vm.load_at(&p, 0);
+    vm.cpu.state.sp = (0xDF, 0xF0);
+
+    let mut callbacks = Callbacks::new();
+    callbacks.on_after_instruction_exec(Box::new(|m, _| m.stop()));
+    vm.start_with_options(0, &mut callbacks);
+
+    let sp = vm.get_register_pair(|cpu| cpu.sp);
+    assert_eq!(0x0123, vm.get_register_pair(|cpu| cpu.pc));
+    assert_eq!(0x0003, vm.ram.read_u16(sp));
+}
+
+#[test]
+fn restart() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    p.add(Instruction(Mnemonic::Nop));
+    p.add(Instruction(Mnemonic::Rst38));
+    vm.load_at(&p, 0);
+    vm.cpu.state.sp = (0xDF, 0xF0);
+
+    let mut callbacks = Callbacks::new();
+    callbacks.on_after_instruction_exec_match(Mnemonic::Rst38, Box::new(|m| m.stop()));
+    vm.start_with_options(0, &mut callbacks);
+
+    let sp = vm.get_register_pair(|cpu| cpu.sp);
+    assert_eq!(0x0038, vm.get_register_pair(|cpu| cpu.pc));
+    assert_eq!(0x0002, vm.ram.read_u16(sp));
+}
+
+#[test]
+fn call_then_return() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    p.add_param_word(Mnemonic::CallXX, 0x0010);
+    vm.load_at(&p, 0);
+    let mut sub = Program::new();
+    sub.add(Instruction(Mnemonic::Ret));
+    vm.load_at(&sub, 0x0010);
+    vm.cpu.state.sp = (0xDF, 0xF0);
+
+    let mut callbacks = Callbacks::new();
+    callbacks.on_after_instruction_exec_match(Mnemonic::Ret, Box::new(|m| m.stop()));
+    vm.start_with_options(0, &mut callbacks);
+
+    assert_eq!(0x0003, vm.get_register_pair(|cpu| cpu.pc));
+    assert_eq!(0xDFF0, vm.get_register_pair(|cpu| cpu.sp));
+}
+
+fn push_then_pop(sp: u16) -> Machine {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    p.add(Instruction(Mnemonic::PushBC));
+    p.add(Instruction(Mnemonic::PopDE));
+    vm.load_at(&p, 0);
+    vm.set_register_pair(|s| &mut s.sp, sp);
+    vm.set_register_pair(|s| &mut s.registers.bc, 0x1234);
+
+    let mut callbacks = Callbacks::new();
+    callbacks.on_after_instruction_exec_match(Mnemonic::PopDE, Box::new(|m| m.stop()));
+    vm.start_with_options(0, &mut callbacks);
+    vm
+}
+
+#[test]
+fn push_then_pop_round_trip() {
+    let vm = push_then_pop(0xDFF0);
+    assert_eq!(0x1234, vm.get_register_pair(|s| s.registers.de));
+    assert_eq!(0xDFF0, vm.get_register_pair(|cpu| cpu.sp));
+    assert_eq!(0x1234, vm.ram.read_u16(0xDFEE));
+}
+
+#[test]
+fn stack_pointer_wraps() {
+    let vm = push_then_pop(0x0000);
+    assert_eq!(0x1234, vm.get_register_pair(|s| s.registers.de));
+    assert_eq!(0x0000, vm.get_register_pair(|cpu| cpu.sp));
+}
+
 #[test]
 fn load() {
     let mut vm = Machine::new();
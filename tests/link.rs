@@ -0,0 +1,105 @@
+extern crate rusty_sms;
+
+use rusty_sms::element::Element::Instruction;
+use rusty_sms::program::Program;
+use rusty_sms::vm::instructions::mnemonics::Mnemonic;
+use rusty_sms::vm::io::link::LinkCable;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::vdp::VdpModel;
+
+fn linked() -> (Machine, Machine) {
+    let mut first = Machine::with_vdp_model(VdpModel::GameGear);
+    let mut second = Machine::with_vdp_model(VdpModel::GameGear);
+    LinkCable::connect(&mut first, &mut second);
+    (first, second)
+}
+
+#[test]
+fn parallel() {
+    let (mut first, mut second) = linked();
+    assert!(first.is_linked());
+    first.write_port(0x02, 0x70);
+    first.write_port(0x01, 0x05);
+    assert_eq!(0x75, second.read_port(0x01));
+
+    second.write_port(0x02, 0x0F);
+    second.write_port(0x01, 0x20);
+    assert_eq!(0x25, first.read_port(0x01));
+
+    first.disconnect_link();
+    assert!(!first.is_linked());
+    assert_eq!(0x2F, second.read_port(0x01));
+}
+
+#[test]
+fn parallel_nmi() {
+    let (mut first, mut second) = linked();
+    second.write_port(0x02, 0xFF);
+    second.read_port(0x01);
+    first.write_port(0x02, 0x3F);
+    first.write_port(0x01, 0x00);
+    assert!(!second.nmi_pending());
+    second.read_port(0x01);
+    assert!(second.nmi_pending());
+}
+
+#[test]
+fn serial() {
+    let (mut first, mut second) = linked();
+    let mut p = Program::new();
+    p.add(Instruction(Mnemonic::Halt));
+    second.load(&p);
+    second.set_register_pair(|s| &mut s.sp, 0xDFF0);
+    first.write_port(0x05, 0x10);
+    second.write_port(0x05, 0x28);
+    first.write_port(0x03, 0xA5);
+    assert_eq!(0x11, first.read_port(0x05));
+    assert_eq!(0x28, second.read_port(0x05));
+
+    first.run_frame();
+    second.run_frame();
+    assert_eq!(0x10, first.read_port(0x05));
+    assert_eq!(0x2A, second.read_port(0x05));
+    assert!(!second.cpu.is_halted());
+    assert_eq!(0x0001, second.ram.read_u16(0xDFEE));
+    assert_eq!(0xA5, second.read_port(0x04));
+    assert_eq!(0x28, second.read_port(0x05));
+}
+
+#[test]
+fn serial_receiver_first() {
+    let (mut first, mut second) = linked();
+    let mut p = Program::new();
+    p.add_param(Mnemonic::LdAX, 0x10);
+    p.add_param(Mnemonic::OutVXA, 0x05);
+    p.add_param(Mnemonic::LdAX, 0xA5);
+    p.add_param(Mnemonic::OutVXA, 0x03);
+    p.add(Instruction(Mnemonic::Halt));
+    first.load(&p);
+    second.write_port(0x05, 0x20);
+
+    // The receiver has finished the frame before the byte is sent, so it picks the byte up
+    // once it catches up with the sender's timestamp.
+    second.run_frame();
+    assert_eq!(0x20, second.read_port(0x05));
+    first.run_frame();
+    assert_eq!(0x22, second.read_port(0x05));
+    assert_eq!(0xA5, second.read_port(0x04));
+
+    second.run_frame();
+    first.run_frame();
+    assert_eq!(0x20, second.read_port(0x05));
+}
+
+#[test]
+fn serial_disabled() {
+    let (mut first, mut second) = linked();
+    first.write_port(0x03, 0xA5);
+    assert_eq!(0x00, first.read_port(0x05));
+    first.write_port(0x05, 0x10);
+    first.write_port(0x03, 0xA5);
+    first.run_frame();
+    second.run_frame();
+    assert_eq!(0x00, second.read_port(0x05));
+    assert_eq!(0xFF, second.read_port(0x04));
+}
@@ -0,0 +1,102 @@
+extern crate rusty_sms;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rusty_sms::element::Element::Instruction;
+use rusty_sms::program::Program;
+use rusty_sms::vm::builder::{MachineBuilder, Preset};
+use rusty_sms::vm::cpu::registers::Registers;
+use rusty_sms::vm::instructions::mnemonics::Mnemonic;
+use rusty_sms::vm::io::open_bus::PortAccess;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::system::System;
+use rusty_sms::vm::vdp::VdpModel;
+
+fn read_port_0x20(vm: &mut Machine) -> u8 {
+    let mut p = Program::new();
+    p.add_param(Mnemonic::InAVX, 0x20);
+    p.add(Instruction(Mnemonic::Halt));
+    vm.load(&p);
+    vm.run_frame();
+    vm.get_register(Registers::a())
+}
+
+#[test]
+fn unmapped_ports() {
+    assert_eq!(0xFF, read_port_0x20(&mut Machine::new()));
+    assert_eq!(
+        0x20,
+        read_port_0x20(&mut Machine::with_vdp_model(VdpModel::Sms1))
+    );
+    assert_eq!(
+        0xFF,
+        read_port_0x20(&mut Machine::with_system(System::Generic))
+    );
+
+    // Port accesses from the host do not drive the CPU's data bus.
+    let mut vm = Machine::with_vdp_model(VdpModel::Sms1);
+    vm.write_port(0x7F, 0x9F);
+    assert_eq!(0xFF, vm.read_port(0x20));
+    assert_eq!(0xFF, vm.data_bus());
+}
+
+#[test]
+fn io_disabled() {
+    let mut vm = Machine::new();
+    vm.write_port(0x3E, 0xAF);
+    assert_eq!(0xFF, vm.read_port(0xDC));
+    vm.set_reset_button(true);
+    assert_eq!(0xFF, vm.read_port(0xDD));
+    vm.write_port(0x3E, 0xAB);
+    assert_eq!(0xEF, vm.read_port(0xDD));
+}
+
+#[test]
+fn unmapped_memory() {
+    let mut vm = MachineBuilder::new().cartridge(&[0x3E, 0x01]).build();
+    assert!(vm.ram.rom_mapped());
+    vm.write_port(0x3E, 0xEB);
+    assert!(!vm.ram.rom_mapped());
+    assert_eq!(0xFF, vm.ram.read_u8(0x0000));
+    vm.write_port(0x3E, 0xAB);
+    assert_eq!(0x3E, vm.ram.read_u8(0x0000));
+}
+
+// Runs `ld a, (0x0100)` from RAM with the cartridge slot disabled.
+fn read_unmapped_rom(preset: Preset) -> (u8, u8) {
+    let mut vm = MachineBuilder::preset(preset).cartridge(&[0x3E]).build();
+    vm.write_port(0x3E, 0xEB);
+    let mut p = Program::new();
+    p.add_param_word(Mnemonic::LdAVXX, 0x0100);
+    p.add(Instruction(Mnemonic::Halt));
+    vm.load_at(&p, 0xC000);
+    vm.cpu.goto(0xC000);
+    vm.run_frame();
+    (vm.get_register(Registers::a()), vm.data_bus())
+}
+
+#[test]
+fn unmapped_memory_reads() {
+    assert_eq!((0x01, 0x76), read_unmapped_rom(Preset::Sms1));
+    assert_eq!((0xFF, 0x76), read_unmapped_rom(Preset::Sms2));
+}
+
+#[test]
+fn callback() {
+    let accesses = Rc::new(RefCell::new(Vec::new()));
+    let log = accesses.clone();
+    let mut vm = MachineBuilder::preset(Preset::Sg1000).build();
+    vm.set_unmapped_port_callback(Box::new(move |_, access| {
+        log.borrow_mut().push(access);
+    }));
+    vm.write_port(0x3E, 0x12);
+    vm.write_port(0x7F, 0x9F);
+    vm.read_port(0x40);
+    vm.read_port(0xBF);
+    assert_eq!(
+        vec![PortAccess::Write(0x3E, 0x12), PortAccess::Read(0x40)],
+        *accesses.borrow()
+    );
+    assert!(vm.take_unmapped_port_callback().is_some());
+}
@@ -0,0 +1,45 @@
+extern crate rusty_sms;
+
+use rusty_sms::vm::builder::MachineBuilder;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::system::System;
+
+// Every byte of a bank holds the bank's number.
+fn banked_rom(banks: usize) -> Vec<u8> {
+    (0..banks)
+        .flat_map(|bank| vec![bank as u8; 0x4000])
+        .collect()
+}
+
+#[test]
+fn large_cartridge() {
+    let mut vm = MachineBuilder::new().cartridge(&banked_rom(8)).build();
+    assert_eq!([0x00, 0x00, 0x01, 0x02], vm.paging_registers());
+    assert_eq!(0x01, vm.ram.read_u8(0x4000));
+    assert_eq!(0x02, vm.ram.read_u8(0x8000));
+
+    vm.write_memory(0xFFFF, 0x05);
+    vm.write_memory(0xFFFE, 0x0B);
+    vm.write_memory(0xFFFD, 0x07);
+    assert_eq!(0x05, vm.ram.read_u8(0x8000));
+    assert_eq!(0x03, vm.ram.read_u8(0x4000));
+    assert_eq!(0x00, vm.ram.read_u8(0x03FF));
+    assert_eq!(0x07, vm.ram.read_u8(0x0400));
+    assert_eq!(0x05, vm.ram.read_u8(0xDFFF));
+
+    vm.hard_reset();
+    assert_eq!([0x00, 0x00, 0x01, 0x02], vm.paging_registers());
+    assert_eq!(0x00, vm.ram.read_u8(0x0400));
+    assert_eq!(0x02, vm.ram.read_u8(0x8000));
+}
+
+#[test]
+fn small_cartridge() {
+    let mut vm = Machine::new();
+    assert!(vm.load_cartridge(&banked_rom(2)));
+    vm.write_memory(0xFFFF, 0x00);
+    assert_eq!(0x01, vm.ram.read_u8(0x4000));
+
+    let mut sg1000 = Machine::with_system(System::Sg1000);
+    assert!(!sg1000.load_cartridge(&banked_rom(4)));
+}
@@ -0,0 +1,98 @@
+extern crate rusty_sms;
+
+use rusty_sms::vm::builder::{MachineBuilder, Preset};
+use rusty_sms::vm::cpu::registers::Registers;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::power::PowerOnPattern;
+use rusty_sms::vm::system::System;
+
+#[test]
+fn hard_reset() {
+    let mut vm = Machine::new();
+    vm.ram.write_u8(0xC000, 0x42);
+    vm.set_register(|s| &mut s.registers.af.0, 0x12);
+    vm.cpu.goto(0x1234);
+    vm.cpu.enable_interrupts();
+    vm.write_port(0xBF, 0x60);
+    vm.write_port(0xBF, 0x81);
+    vm.write_port(0x7F, 0x90);
+    vm.write_port(0x3F, 0x55);
+
+    vm.hard_reset();
+    assert_eq!(0x0000, vm.get_register_pair(|s| s.pc));
+    assert!(!vm.cpu.interrupts_enabled());
+    assert_eq!(0x12, vm.get_register(Registers::a()));
+    assert_eq!(0x42, vm.ram.read_u8(0xC000));
+    assert_eq!(0x00, vm.vdp.register(1));
+    assert_eq!(0x0F, vm.psg.attenuation(0));
+    assert_eq!(0xFF, vm.io_control());
+}
+
+#[test]
+fn reset_button() {
+    let mut vm = MachineBuilder::preset(Preset::Sms1).build();
+    vm.cpu.goto(0x1234);
+    vm.write_port(0xBF, 0x60);
+    vm.write_port(0xBF, 0x81);
+    vm.reset();
+    assert_eq!(0x1234, vm.get_register_pair(|s| s.pc));
+    assert_eq!(0x60, vm.vdp.register(1));
+    assert_eq!(0x00, vm.read_port(0xDD) & 0x10);
+    vm.run_frame();
+    assert_eq!(0x10, vm.read_port(0xDD) & 0x10);
+
+    let mut sms2 = MachineBuilder::new().build();
+    sms2.reset();
+    assert_eq!(0x10, sms2.read_port(0xDD) & 0x10);
+    sms2.run_frame();
+    assert_eq!(0x10, sms2.read_port(0xDD) & 0x10);
+
+    let mut sc3000 = MachineBuilder::new().system(System::Sc3000).build();
+    sc3000.reset();
+    assert!(sc3000.nmi_pending());
+
+    let mut z80 = MachineBuilder::preset(Preset::GenericZ80).build();
+    z80.cpu.goto(0x1234);
+    z80.reset();
+    assert_eq!(0x0000, z80.get_register_pair(|s| s.pc));
+}
+
+#[test]
+fn power_on_patterns() {
+    let mut vm = Machine::new();
+    vm.set_power_on_pattern(PowerOnPattern::Ones);
+    vm.power_cycle();
+    assert_eq!(0xFF, vm.ram.read_u8(0x8000));
+    assert_eq!(0xFFFF, vm.get_register_pair(Registers::af()));
+    assert_eq!(0xFFFF, vm.get_register_pair(Registers::sp()));
+    assert_eq!(0x0000, vm.get_register_pair(|s| s.pc));
+
+    let random = |seed| {
+        let mut vm = MachineBuilder::new()
+            .power_on_pattern(PowerOnPattern::Random(seed))
+            .cartridge(&[0x3E])
+            .build();
+        let ram: Vec<u8> = (0xC000..0xC100).map(|a| vm.ram.read_u8(a)).collect();
+        vm.power_cycle();
+        assert_eq!(0x3E, vm.ram.read_u8(0x0000));
+        ram
+    };
+    assert_eq!(random(1), random(1));
+    let vm = MachineBuilder::new()
+        .power_on_pattern(PowerOnPattern::Random(1))
+        .build();
+    assert!((0x0000..0xC000).all(|a| vm.ram.read_u8(a) == 0xFF));
+    assert_ne!(random(1), random(2));
+    assert!(random(0).iter().any(|value| *value != random(0)[0]));
+}
+
+#[test]
+fn return_with_power_on_stack_pointer() {
+    let mut vm = MachineBuilder::new()
+        .power_on_pattern(PowerOnPattern::Ones)
+        .cartridge(&[0xC9])
+        .build();
+    assert_eq!(0xFFFF, vm.get_register_pair(Registers::sp()));
+    vm.run_frame();
+    assert_ne!(0xFFFF, vm.get_register_pair(Registers::sp()));
+}
@@ -0,0 +1,131 @@
+extern crate rusty_sms;
+
+use rusty_sms::program::Program;
+use rusty_sms::vm::audio::stereo_buffer::StereoBuffer;
+use rusty_sms::vm::instructions::mnemonics::Mnemonic;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::psg::Psg;
+use rusty_sms::vm::vdp::VdpModel;
+
+const CLOCK: u32 = 3_579_545;
+
+fn run(psg: &mut Psg, writes: &[u8], cycles: u64) -> Vec<i16> {
+    let mut buffer = StereoBuffer::new(CLOCK, 44_100);
+    for value in writes {
+        psg.write(0, *value, &mut buffer);
+    }
+    psg.run_until(cycles, &mut buffer);
+    buffer.end_frame(cycles);
+    buffer.take_samples().into_iter().step_by(2).collect()
+}
+
+fn rising_edges(samples: &[i16]) -> usize {
+    let mut high = true;
+    let mut edges = 0;
+    for sample in samples {
+        if high && *sample < -4000 {
+            high = false;
+        } else if !high && *sample > 4000 {
+            high = true;
+            edges += 1;
+        }
+    }
+    edges
+}
+
+#[test]
+fn latch_and_data() {
+    let mut psg = Psg::new();
+    run(&mut psg, &[0x8E, 0x0F], 0);
+    assert_eq!(0xFE, psg.tone_period(0));
+
+    run(&mut psg, &[0xB5], 0);
+    assert_eq!(0x05, psg.attenuation(1));
+    run(&mut psg, &[0x0A], 0);
+    assert_eq!(0x0A, psg.attenuation(1));
+
+    run(&mut psg, &[0xE5], 0);
+    assert_eq!(0x05, psg.noise_control());
+}
+
+#[test]
+fn tone() {
+    let mut psg = Psg::new();
+    let samples = run(&mut psg, &[0x8E, 0x0F, 0x90], CLOCK as u64);
+    assert_eq!(44_100, samples.len());
+    let edges = rising_edges(&samples);
+    assert!((438..=442).contains(&edges), "{} edges.", edges);
+}
+
+#[test]
+fn silence() {
+    let mut psg = Psg::new();
+    let samples = run(&mut psg, &[0x81], CLOCK as u64 / 10);
+    assert!(samples.iter().all(|s| *s == 0));
+}
+
+#[test]
+fn noise() {
+    let mut psg = Psg::new();
+    let samples = run(&mut psg, &[0xE4, 0xF0], CLOCK as u64 / 10);
+    assert!(samples.iter().any(|s| *s > 0));
+    assert!(samples.iter().any(|s| *s < 0));
+}
+
+#[test]
+fn port_writes() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    for value in [0x8E, 0x0F, 0x90].iter() {
+        p.add_param(Mnemonic::LdAX, *value);
+        p.add_param(Mnemonic::OutVXA, 0x7F);
+    }
+    vm.load(&p);
+
+    let frame = vm.run_frame();
+    assert_eq!(0xFE, vm.psg.tone_period(0));
+    assert_eq!(0x00, vm.psg.attenuation(0));
+    assert!(rising_edges(&frame.audio) > 0);
+}
+
+#[test]
+fn game_gear_stereo() {
+    let mut vm = Machine::with_vdp_model(VdpModel::GameGear);
+    let mut p = Program::new();
+    for (value, port) in [(0x8E, 0x7F), (0x0F, 0x7F), (0x90, 0x7F), (0xF0, 0x06)].iter() {
+        p.add_param(Mnemonic::LdAX, *value);
+        p.add_param(Mnemonic::OutVXA, *port);
+    }
+    vm.load(&p);
+
+    let frame = vm.run_frame();
+    assert_eq!(0xF0, vm.psg.stereo());
+    let left: Vec<i16> = frame.audio.iter().step_by(2).cloned().collect();
+    let right: Vec<i16> = frame.audio.iter().skip(1).step_by(2).cloned().collect();
+    assert!(rising_edges(&left) > 0);
+    assert!(rising_edges(&right[100..]) == 0);
+}
+
+#[test]
+fn master_system_mono() {
+    let mut vm = Machine::new();
+    vm.write_port(0x06, 0xF0);
+    assert_eq!(0xFF, vm.psg.stereo());
+    vm.write_port(0x7F, 0x90);
+
+    let frame = vm.run_frame();
+    let mut pairs = frame.audio.chunks(2);
+    assert!(pairs.all(|pair| pair[0] == pair[1]));
+}
+
+#[test]
+fn mute_mask() {
+    let mut psg = Psg::new();
+    psg.set_mute_mask(0x01);
+    let samples = run(&mut psg, &[0x8E, 0x0F, 0x90], CLOCK as u64 / 10);
+    assert!(samples.iter().all(|s| *s == 0));
+
+    psg.set_mute_mask(0x0E);
+    let samples = run(&mut psg, &[], CLOCK as u64 / 5);
+    assert!(rising_edges(&samples) > 0);
+}
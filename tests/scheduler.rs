@@ -0,0 +1,92 @@
+extern crate rusty_sms;
+
+use rusty_sms::element::Element::Instruction;
+use rusty_sms::program::Program;
+use rusty_sms::vm::cpu::registers::Registers;
+use rusty_sms::vm::instructions::mnemonics::Mnemonic;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::vdp::TvSystem;
+
+fn set_vdp_register(vm: &mut Machine, register: u8, value: u8) {
+    vm.vdp.write_control(value);
+    vm.vdp.write_control(0x80 | register);
+}
+
+#[test]
+fn frame_length() {
+    let mut vm = Machine::new();
+    vm.run_frame();
+    assert_eq!(262 * 228, vm.cycles());
+
+    vm.set_tv_system(TvSystem::Pal);
+    vm.run_frame();
+    assert_eq!(262 * 228 + 313 * 228, vm.cycles());
+}
+
+#[test]
+fn frame_audio() {
+    let mut vm = Machine::new();
+    let frame = vm.run_frame();
+    assert_eq!(2 * 735, frame.audio.len());
+
+    vm.set_sample_rate(48_000);
+    let frame = vm.run_frame();
+    assert_eq!(2 * 801, frame.audio.len());
+}
+
+#[test]
+fn frame_interrupt() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    p.add_param_word(Mnemonic::LdSPXX, 0xDFF0);
+    p.add_param(Mnemonic::LdAX, 0x20);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add_param(Mnemonic::LdAX, 0x81);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add(Instruction(Mnemonic::Ei));
+    p.add(Instruction(Mnemonic::Halt));
+    vm.load(&p);
+
+    let mut handler = Program::new();
+    handler.add_param(Mnemonic::InAVX, 0xBF);
+    handler.add_param(Mnemonic::LdBX, 0x42);
+    handler.add(Instruction(Mnemonic::Halt));
+    vm.load_at(&handler, 0x0038);
+
+    vm.run_frame();
+    assert_eq!(0x42, vm.get_register(Registers::b()));
+    assert_eq!(0x80, vm.get_register(Registers::a()) & 0x80);
+    assert!(vm.cpu.is_halted());
+    assert!(!vm.cpu.interrupts_enabled());
+}
+
+#[test]
+fn background() {
+    let mut vm = Machine::new();
+    set_vdp_register(&mut vm, 0, 0x04);
+    set_vdp_register(&mut vm, 1, 0x40);
+    set_vdp_register(&mut vm, 2, 0xFF);
+    set_vdp_register(&mut vm, 5, 0xFF);
+
+    vm.vdp.write_control(0x00);
+    vm.vdp.write_control(0x7F);
+    vm.vdp.write_data(0xD0);
+
+    vm.vdp.write_control(0x01);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x03);
+
+    vm.vdp.write_control(0x00);
+    vm.vdp.write_control(0x40);
+    for _ in 0..8 {
+        vm.vdp.write_data(0xFF);
+        vm.vdp.write_data(0x00);
+        vm.vdp.write_data(0x00);
+        vm.vdp.write_data(0x00);
+    }
+
+    let frame = vm.run_frame();
+    assert_eq!(256, frame.video.width());
+    assert_eq!(192, frame.video.height());
+    assert!(frame.video.pixels().iter().all(|p| *p == 0xFF0000));
+}
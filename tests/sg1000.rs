@@ -0,0 +1,84 @@
+extern crate rusty_sms;
+
+use rusty_sms::vm::io::joypad::Joypad;
+use rusty_sms::vm::io::keyboard::Key;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::ram::MemoryMap;
+use rusty_sms::vm::system::System;
+use rusty_sms::vm::vdp::{DisplayMode, VdpModel};
+
+#[test]
+fn memory_map() {
+    let mut vm = Machine::with_system(System::Sg1000);
+    assert_eq!(VdpModel::Tms9918a, vm.vdp.model());
+    assert!(vm.load_cartridge(&[0x3E, 0x12]));
+    assert!(!vm.load_cartridge(&[0; 0xC001]));
+
+    vm.ram.write_u8(0x0000, 0xFF);
+    assert_eq!(0x3E, vm.ram.read_u8(0x0000));
+    vm.ram.write_u8(0xC005, 0x55);
+    assert_eq!(0x55, vm.ram.read_u8(0xC405));
+    assert_eq!(0x55, vm.ram.read_u8(0xFC05));
+
+    vm.ram.set_map(MemoryMap::SC3000_BASIC);
+    vm.ram.write_u8(0x8000, 0xAA);
+    assert_eq!(0xAA, vm.ram.read_u8(0x8000));
+    assert_eq!(0x00, vm.ram.read_u8(0xC000));
+}
+
+#[test]
+fn no_mode4() {
+    let mut vm = Machine::with_system(System::Sg1000);
+    vm.write_port(0xBF, 0x06);
+    vm.write_port(0xBF, 0x80);
+    assert_eq!(DisplayMode::Graphics2, vm.vdp.display_mode());
+}
+
+#[test]
+fn sg1000_ports() {
+    let mut vm = Machine::with_system(System::Sg1000);
+    vm.set_joypad(
+        0,
+        Joypad {
+            button1: true,
+            ..Joypad::new()
+        },
+    );
+    assert_eq!(0xEF, vm.read_port(0xDC));
+    assert_eq!(0xEF, vm.read_port(0xC0));
+    assert_eq!(0xFF, vm.read_port(0x7E));
+
+    vm.write_port(0x7F, 0x9A);
+    assert_eq!(0x0A, vm.psg.attenuation(0));
+}
+
+#[test]
+fn keyboard_matrix() {
+    let mut vm = Machine::with_system(System::Sc3000);
+    vm.set_key(Key::A, true);
+    vm.set_key(Key::Digit8, true);
+    assert!(vm.key_pressed(Key::A));
+
+    vm.write_port(0xDF, 0x92);
+    vm.write_port(0xDE, 0x00);
+    assert_eq!(0xFB, vm.read_port(0xDC));
+    assert_eq!(0xFE, vm.read_port(0xDD));
+
+    vm.write_port(0xDF, 0x03);
+    assert_eq!(0x02, vm.read_port(0xDE));
+    assert_eq!(0xFF, vm.read_port(0xDC));
+
+    vm.set_joypad(
+        1,
+        Joypad {
+            up: true,
+            ..Joypad::new()
+        },
+    );
+    vm.write_port(0xDE, 0x07);
+    assert_eq!(0xBF, vm.read_port(0xDC));
+
+    vm.release_keys();
+    vm.write_port(0xDE, 0x00);
+    assert_eq!(0xFF, vm.read_port(0xDC));
+}
@@ -0,0 +1,466 @@
+extern crate rusty_sms;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rusty_sms::element::Element::Instruction;
+use rusty_sms::program::Program;
+use rusty_sms::vm::callbacks::Callbacks;
+use rusty_sms::vm::glasses::{Eye, GlassesMode};
+use rusty_sms::vm::instructions::mnemonics::Mnemonic;
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::system::System;
+use rusty_sms::vm::vdp::framebuffer::Framebuffer;
+use rusty_sms::vm::vdp::{DisplayMode, VdpModel, Viewport};
+
+fn set_register(vm: &mut Machine, register: u8, value: u8) {
+    vm.vdp.write_control(value);
+    vm.vdp.write_control(0x80 | register);
+}
+
+fn write_vram(vm: &mut Machine, address: u16, data: &[u8]) {
+    vm.vdp.write_control(address as u8);
+    vm.vdp.write_control(0x40 | (address >> 8) as u8);
+    for value in data {
+        vm.vdp.write_data(*value);
+    }
+}
+
+#[test]
+fn graphics1() {
+    let mut vm = Machine::new();
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 2, 0x0E);
+    set_register(&mut vm, 3, 0x80);
+    set_register(&mut vm, 4, 0x00);
+    set_register(&mut vm, 5, 0x7E);
+    set_register(&mut vm, 7, 0x01);
+    assert_eq!(DisplayMode::Graphics1, vm.vdp.display_mode());
+
+    write_vram(&mut vm, 0x0000, &[0xF0; 8]);
+    write_vram(&mut vm, 0x2000, &[0xF4]);
+    write_vram(&mut vm, 0x3F00, &[0xD0]);
+
+    let frame = vm.run_frame();
+    assert_eq!(0xFFFFFF, frame.video.pixel(0, 0));
+    assert_eq!(0x5455ED, frame.video.pixel(4, 0));
+    assert_eq!(0xFFFFFF, frame.video.pixel(248, 191));
+}
+
+#[test]
+fn text() {
+    let mut vm = Machine::new();
+    set_register(&mut vm, 1, 0x50);
+    set_register(&mut vm, 2, 0x0E);
+    set_register(&mut vm, 7, 0xF4);
+    assert_eq!(DisplayMode::Text, vm.vdp.display_mode());
+
+    write_vram(&mut vm, 0x0000, &[0xFC; 8]);
+
+    let frame = vm.run_frame();
+    assert_eq!(0x5455ED, frame.video.pixel(0, 0));
+    assert_eq!(0xFFFFFF, frame.video.pixel(8, 0));
+    assert_eq!(0xFFFFFF, frame.video.pixel(247, 0));
+    assert_eq!(0x5455ED, frame.video.pixel(248, 0));
+}
+
+#[test]
+fn fifth_sprite() {
+    let mut vm = Machine::new();
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 2, 0x0E);
+    set_register(&mut vm, 3, 0x80);
+    set_register(&mut vm, 5, 0x7E);
+    set_register(&mut vm, 6, 0x00);
+
+    let mut table = Vec::new();
+    for sprite in 0..6 {
+        table.extend_from_slice(&[0x0F, sprite * 16, 0x00, 0x0F]);
+    }
+    table.push(0xD0);
+    write_vram(&mut vm, 0x3F00, &table);
+    write_vram(&mut vm, 0x0000, &[0xFF; 8]);
+
+    let frame = vm.run_frame();
+    let status = vm.vdp.read_control();
+    assert_eq!(0x40, status & 0x40);
+    assert_eq!(4, status & 0x1F);
+    assert_eq!(0xFFFFFF, frame.video.pixel(48, 16));
+    assert_eq!(0x000000, frame.video.pixel(64, 16));
+}
+
+#[test]
+fn game_gear_palette() {
+    let mut vm = Machine::with_vdp_model(VdpModel::GameGear);
+    set_register(&mut vm, 0, 0x04);
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 2, 0xFF);
+    set_register(&mut vm, 5, 0xFF);
+
+    write_vram(&mut vm, 0x3F00, &[0xD0]);
+    write_vram(&mut vm, 0x0000, &[0xFF, 0x00, 0x00, 0x00].repeat(8));
+
+    vm.vdp.write_control(0x02);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x0F);
+    assert_eq!(&[0x00, 0x00], &vm.vdp.cram()[2..4]);
+    vm.vdp.write_data(0x0F);
+    assert_eq!(&[0x0F, 0x0F], &vm.vdp.cram()[2..4]);
+
+    let frame = vm.run_frame();
+    assert_eq!(160, frame.video.width());
+    assert_eq!(144, frame.video.height());
+    assert!(frame.video.pixels().iter().all(|p| *p == 0xFF00FF));
+
+    let full = vm.framebuffer(Viewport::Full);
+    assert_eq!(256, full.width());
+    assert_eq!(192, full.height());
+}
+
+#[test]
+fn master_gear() {
+    let mut vm = Machine::with_system(System::MasterGear);
+    assert!(vm.vdp.sms_mode());
+    set_register(&mut vm, 0, 0x04);
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 2, 0xFF);
+    set_register(&mut vm, 5, 0xFF);
+
+    write_vram(&mut vm, 0x3F00, &[0xD0]);
+    write_vram(&mut vm, 0x0000, &[0xAA, 0x00, 0x00, 0x00].repeat(8));
+
+    vm.vdp.write_control(0x01);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x03);
+    assert_eq!(0x03, vm.vdp.cram()[1]);
+
+    let frame = vm.run_frame();
+    assert_eq!(160, frame.video.width());
+    assert_eq!(144, frame.video.height());
+    assert_eq!(0x9F0000, frame.video.pixel(0, 0));
+    assert_eq!(0x9F0000, frame.video.pixel(1, 0));
+    assert_eq!(0x600000, frame.video.pixel(3, 0));
+    assert_eq!(0xFF0000, vm.framebuffer(Viewport::Full).pixel(0, 0));
+
+    assert_eq!(0xFF, vm.read_port(0x00));
+    vm.set_start_button(true);
+    assert!(vm.nmi_pending());
+}
+
+#[test]
+fn master_gear_line_groups() {
+    let mut vm = Machine::with_system(System::MasterGear);
+    set_register(&mut vm, 0, 0x04);
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 2, 0xFF);
+    set_register(&mut vm, 5, 0xFF);
+
+    write_vram(&mut vm, 0x3F00, &[0xD0]);
+    let mut tile = vec![0xFF, 0x00, 0x00, 0x00];
+    tile.extend_from_slice(&[0x00; 28]);
+    write_vram(&mut vm, 0x0000, &tile);
+
+    vm.vdp.write_control(0x01);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x03);
+
+    let frame = vm.run_frame();
+    let column: Vec<u32> = (0..6).map(|y| frame.video.pixel(0, y)).collect();
+    assert_eq!(
+        vec![0xBF0000, 0x000000, 0x000000, 0x000000, 0x000000, 0x000000],
+        column
+    );
+}
+
+fn name_table_mirror(model: VdpModel) -> u32 {
+    let mut vm = Machine::with_vdp_model(model);
+    set_register(&mut vm, 0, 0x04);
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 2, 0xFE);
+    set_register(&mut vm, 5, 0xFF);
+
+    write_vram(&mut vm, 0x3F00, &[0xD0]);
+    write_vram(&mut vm, 0x0020, &[0xFF, 0x00, 0x00, 0x00].repeat(8));
+    write_vram(&mut vm, 0x3C00, &[0x01, 0x00].repeat(32));
+    vm.vdp.write_control(0x01);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x03);
+
+    vm.run_frame().video.pixel(0, 128)
+}
+
+#[test]
+fn sms1_name_table_mask() {
+    assert_eq!(0x000000, name_table_mirror(VdpModel::Sms1));
+    assert_eq!(0xFF0000, name_table_mirror(VdpModel::Sms2));
+}
+
+fn zoomed_sprite_width(model: VdpModel) -> usize {
+    let mut vm = Machine::with_vdp_model(model);
+    set_register(&mut vm, 0, 0x04);
+    set_register(&mut vm, 1, 0x41);
+    set_register(&mut vm, 2, 0xFF);
+    set_register(&mut vm, 5, 0xFF);
+    set_register(&mut vm, 6, 0xFB);
+
+    let mut table = vec![0x0F; 6];
+    table.push(0xD0);
+    write_vram(&mut vm, 0x3F00, &table);
+    let positions: Vec<u8> = (0..6).flat_map(|i| vec![i * 32, 0x01]).collect();
+    write_vram(&mut vm, 0x3F80, &positions);
+    write_vram(&mut vm, 0x0020, &[0xFF, 0x00, 0x00, 0x00].repeat(8));
+    vm.vdp.write_control(0x11);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x3F);
+
+    let frame = vm.run_frame();
+    (160..192)
+        .filter(|x| frame.video.pixel(*x, 16) == 0xFFFFFF)
+        .count()
+}
+
+#[test]
+fn sms1_sprite_zoom() {
+    assert_eq!(8, zoomed_sprite_width(VdpModel::Sms1));
+    assert_eq!(16, zoomed_sprite_width(VdpModel::Sms2));
+}
+
+#[test]
+fn mega_drive_tms_modes() {
+    let mut vm = Machine::with_vdp_model(VdpModel::MegaDrive);
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 7, 0x0F);
+    assert!(!vm.vdp.model().supports_tms_modes());
+
+    vm.vdp.write_control(0x1F);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x30);
+
+    let frame = vm.run_frame();
+    assert!(frame.video.pixels().iter().all(|p| *p == 0x0000FF));
+}
+
+fn v_counter_sequence(vm: &mut Machine) -> Vec<u8> {
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let recorder = values.clone();
+    let mut callbacks = Callbacks::new();
+    callbacks.on_before_instruction_fetch(Box::new(move |m| {
+        let value = m.vdp.read_v_counter();
+        let mut values = recorder.borrow_mut();
+        if values.last() != Some(&value) {
+            values.push(value);
+        }
+    }));
+    vm.run_frame_with(&mut callbacks);
+    let result = values.borrow().clone();
+    result
+}
+
+#[test]
+fn extended_height_224() {
+    let mut vm = Machine::new();
+    set_register(&mut vm, 0, 0x06);
+    set_register(&mut vm, 1, 0x50);
+    set_register(&mut vm, 2, 0xFF);
+    set_register(&mut vm, 5, 0xFF);
+    assert_eq!(224, vm.vdp.active_height());
+
+    write_vram(&mut vm, 0x0020, &[0xFF, 0x00, 0x00, 0x00].repeat(8));
+    write_vram(&mut vm, 0x3700 + 27 * 64, &[0x01, 0x00]);
+    write_vram(&mut vm, 0x3F00, &[0xD0, 0xD0]);
+    vm.vdp.write_control(0x01);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(0x03);
+
+    let sequence = v_counter_sequence(&mut vm);
+    let jump = sequence.iter().position(|v| *v == 0xEA).unwrap();
+    assert_eq!(0xE5, sequence[jump + 1]);
+
+    let frame = vm.framebuffer(Viewport::Full);
+    assert_eq!(224, frame.height());
+    assert_eq!(0xFF0000, frame.pixel(0, 216));
+}
+
+#[test]
+fn extended_height_unsupported() {
+    let mut vm = Machine::with_vdp_model(VdpModel::Sms1);
+    set_register(&mut vm, 0, 0x06);
+    set_register(&mut vm, 1, 0x48);
+    assert_eq!(192, vm.vdp.active_height());
+
+    let mut vm = Machine::new();
+    set_register(&mut vm, 0, 0x06);
+    set_register(&mut vm, 1, 0x48);
+    assert_eq!(240, vm.vdp.active_height());
+}
+
+fn mid_line_palette_write(accurate: bool) -> Framebuffer {
+    let mut vm = Machine::new();
+    vm.vdp.set_accurate_timing(accurate);
+    set_register(&mut vm, 0, 0x04);
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 2, 0xFF);
+    set_register(&mut vm, 5, 0xFF);
+    write_vram(&mut vm, 0x3F00, &[0xD0]);
+
+    let mut p = Program::new();
+    p.add_param(Mnemonic::LdAX, 0x00);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add_param(Mnemonic::LdAX, 0xC0);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add_param(Mnemonic::LdAX, 0x03);
+    for _ in 0..10 {
+        p.add(Instruction(Mnemonic::Nop));
+    }
+    p.add_param(Mnemonic::OutVXA, 0xBE);
+    p.add(Instruction(Mnemonic::Halt));
+    vm.load(&p);
+
+    vm.run_frame().video
+}
+
+#[test]
+fn mid_line_palette() {
+    let frame = mid_line_palette_write(true);
+    assert_eq!(0x000000, frame.pixel(140, 0));
+    assert_eq!(0xFF0000, frame.pixel(141, 0));
+    assert_eq!(0xFF0000, frame.pixel(0, 1));
+
+    let frame = mid_line_palette_write(false);
+    assert_eq!(0xFF0000, frame.pixel(0, 0));
+}
+
+#[test]
+fn vram_access_slots() {
+    let mut vm = Machine::new();
+    vm.vdp.set_accurate_timing(true);
+    set_register(&mut vm, 1, 0x40);
+
+    let mut p = Program::new();
+    p.add_param(Mnemonic::LdAX, 0x00);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add_param(Mnemonic::LdAX, 0x40);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    for value in 1..4 {
+        p.add_param(Mnemonic::LdAX, value);
+        p.add_param(Mnemonic::OutVXA, 0xBE);
+    }
+    p.add(Instruction(Mnemonic::Halt));
+    vm.load(&p);
+
+    vm.run_frame();
+    assert_eq!(2, vm.vdp.delayed_writes());
+    assert_eq!(&[0x01, 0x02, 0x03], &vm.vdp.vram()[0..3]);
+}
+
+#[test]
+fn sprite_flags_once_per_line() {
+    let mut vm = Machine::new();
+    set_register(&mut vm, 0, 0x04);
+    set_register(&mut vm, 1, 0x40);
+    set_register(&mut vm, 5, 0xFF);
+    write_vram(&mut vm, 0x0000, &[0xFF; 32]);
+    write_vram(&mut vm, 0x3F00, &[0xFF, 0xFF, 0xD0]);
+    write_vram(&mut vm, 0x3F80, &[0x00; 4]);
+    vm.vdp.set_accurate_timing(true);
+
+    let mut p = Program::new();
+    p.add_param(Mnemonic::LdAX, 0x00);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add_param(Mnemonic::LdAX, 0xC0);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add_param(Mnemonic::OutVXA, 0xBE);
+    p.add_param(Mnemonic::InAVX, 0xBF);
+    p.add_param_word(Mnemonic::LdVXXA, 0xC000);
+    p.add_param(Mnemonic::OutVXA, 0xBE);
+    p.add_param(Mnemonic::InAVX, 0xBF);
+    p.add_param_word(Mnemonic::LdVXXA, 0xC001);
+    p.add(Instruction(Mnemonic::Halt));
+    vm.load(&p);
+
+    vm.run_frame();
+    assert_eq!(0x20, vm.ram.read_u8(0xC000) & 0x20);
+    assert_eq!(0x00, vm.ram.read_u8(0xC001) & 0x20);
+}
+
+fn backdrop_frame(vm: &mut Machine, colour: u8, shutter: u8) -> Option<Eye> {
+    vm.vdp.write_control(0x10);
+    vm.vdp.write_control(0xC0);
+    vm.vdp.write_data(colour);
+    vm.write_memory(0xFFFB, shutter);
+    vm.run_frame().eye
+}
+
+#[test]
+fn segascope_glasses() {
+    let mut vm = Machine::new();
+    set_register(&mut vm, 0, 0x04);
+    assert_eq!(None, vm.run_frame().eye);
+
+    assert_eq!(Some(Eye::Left), backdrop_frame(&mut vm, 0x03, 0x01));
+    assert_eq!(Some(Eye::Right), backdrop_frame(&mut vm, 0x30, 0x00));
+
+    let left = vm.glasses_framebuffer(GlassesMode::LeftOnly);
+    assert_eq!(0xFF0000, left.pixel(10, 10));
+    let right = vm.glasses_framebuffer(GlassesMode::RightOnly);
+    assert_eq!(0x0000FF, right.pixel(10, 10));
+    let anaglyph = vm.glasses_framebuffer(GlassesMode::Anaglyph);
+    assert_eq!(0xFF00FF, anaglyph.pixel(10, 10));
+    let side_by_side = vm.glasses_framebuffer(GlassesMode::SideBySide);
+    assert_eq!(512, side_by_side.width());
+    assert_eq!(0xFF0000, side_by_side.pixel(10, 10));
+    assert_eq!(0x0000FF, side_by_side.pixel(266, 10));
+}
+
+#[test]
+fn segascope_glasses_height_change() {
+    let mut vm = Machine::new();
+    set_register(&mut vm, 0, 0x06);
+    set_register(&mut vm, 1, 0x10);
+    assert_eq!(Some(Eye::Left), backdrop_frame(&mut vm, 0x03, 0x01));
+    set_register(&mut vm, 0, 0x04);
+    set_register(&mut vm, 1, 0x00);
+    assert_eq!(Some(Eye::Right), backdrop_frame(&mut vm, 0x30, 0x00));
+    assert_eq!(224, vm.glasses_framebuffer(GlassesMode::LeftOnly).height());
+
+    let anaglyph = vm.glasses_framebuffer(GlassesMode::Anaglyph);
+    assert_eq!(192, anaglyph.height());
+    assert_eq!(0xFF00FF, anaglyph.pixel(10, 191));
+    let side_by_side = vm.glasses_framebuffer(GlassesMode::SideBySide);
+    assert_eq!(192, side_by_side.height());
+    assert_eq!(0x0000FF, side_by_side.pixel(266, 191));
+}
+
+#[test]
+fn segascope_shutter_in_vblank() {
+    let mut vm = Machine::new();
+    let mut p = Program::new();
+    p.add_param_word(Mnemonic::LdSPXX, 0xDFF0);
+    p.add_param(Mnemonic::LdAX, 0x60);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add_param(Mnemonic::LdAX, 0x81);
+    p.add_param(Mnemonic::OutVXA, 0xBF);
+    p.add(Instruction(Mnemonic::Ei));
+    p.add(Instruction(Mnemonic::Halt));
+    p.add_param(Mnemonic::JrX, 0xFD);
+    vm.load(&p);
+
+    // The handler counts frames and writes the count to the shutter, flipping it during each
+    // vertical blank.
+    let mut handler = Program::new();
+    handler.add_param(Mnemonic::InAVX, 0xBF);
+    handler.add_param_word(Mnemonic::LdHLXX, 0xC000);
+    handler.add(Instruction(Mnemonic::IncVHL));
+    handler.add_param_word(Mnemonic::LdAVXX, 0xC000);
+    handler.add_param_word(Mnemonic::LdVXXA, 0xFFFB);
+    handler.add(Instruction(Mnemonic::Ei));
+    handler.add(Instruction(Mnemonic::Ret));
+    vm.load_at(&handler, 0x0038);
+
+    let eyes: Vec<Option<Eye>> = (0..4).map(|_| vm.run_frame().eye).collect();
+    assert_eq!(
+        vec![None, Some(Eye::Left), Some(Eye::Right), Some(Eye::Left)],
+        eyes
+    );
+    assert_eq!(Some(0x04), vm.glasses_shutter());
+}
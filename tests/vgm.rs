@@ -0,0 +1,211 @@
+extern crate rusty_sms;
+
+use rusty_sms::vm::machine::Machine;
+use rusty_sms::vm::vgm::player::{VgmError, VgmPlayer};
+
+fn read_u32(file: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&file[offset..offset + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn samples(cycles: u64) -> u32 {
+    (cycles * 44_100 / 3_579_545) as u32
+}
+
+#[test]
+fn header() {
+    let mut vm = Machine::new();
+    vm.start_vgm_log();
+    assert!(vm.is_logging_vgm());
+    vm.vgm.as_mut().unwrap().tags.game = "Test".to_string();
+    vm.run_frame();
+    vm.run_frame();
+    let file = vm.stop_vgm_log().unwrap();
+    assert!(!vm.is_logging_vgm());
+
+    assert_eq!(b"Vgm ", &file[0..4]);
+    assert_eq!(file.len() - 4, read_u32(&file, 0x04) as usize);
+    assert_eq!(0x151, read_u32(&file, 0x08));
+    assert_eq!(3_579_545, read_u32(&file, 0x0C));
+    assert_eq!(0, read_u32(&file, 0x10));
+    assert_eq!(samples(2 * 262 * 228), read_u32(&file, 0x18));
+    assert_eq!(0, read_u32(&file, 0x1C));
+    assert_eq!(60, read_u32(&file, 0x24));
+
+    let gd3 = 0x14 + read_u32(&file, 0x14) as usize;
+    assert_eq!(b"Gd3 ", &file[gd3..gd3 + 4]);
+    assert_eq!(0x66, file[gd3 - 1]);
+    let game: Vec<u8> = "Test"
+        .encode_utf16()
+        .flat_map(|u| u.to_le_bytes().to_vec())
+        .collect();
+    assert_eq!(&game[..], &file[gd3 + 16..gd3 + 24]);
+}
+
+#[test]
+fn commands() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.run_frame();
+    vm.start_vgm_log();
+    vm.run_frame();
+    vm.write_port(0x7F, 0x9F);
+    vm.write_port(0xF0, 0x30);
+    vm.write_port(0xF1, 0x1F);
+    vm.mark_vgm_loop();
+    vm.run_frame();
+    let file = vm.stop_vgm_log().unwrap();
+
+    assert_eq!(3_579_545, read_u32(&file, 0x10));
+    let data = 0x34 + read_u32(&file, 0x34) as usize;
+    let commands = &file[data..];
+    let log = [0x62, 0x50, 0x9F, 0x51, 0x30, 0x1F];
+    let start = commands.windows(log.len()).position(|w| w == log).unwrap();
+
+    let loop_offset = 0x1C + read_u32(&file, 0x1C) as usize;
+    assert_eq!(data + start + log.len(), loop_offset);
+    assert_eq!(0x61, file[loop_offset]);
+    assert_eq!(0x66, file[loop_offset + 3]);
+    let total = samples(2 * 262 * 228);
+    assert_eq!(total - samples(262 * 228), read_u32(&file, 0x20));
+    assert_eq!(total, read_u32(&file, 0x18));
+}
+
+fn record_tone(frames: usize) -> Vec<u8> {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.start_vgm_log();
+    vm.vgm.as_mut().unwrap().tags.track = "Tone".to_string();
+    for value in [0x8E, 0x0F, 0x90].iter() {
+        vm.write_port(0x7F, *value);
+    }
+    vm.write_port(0xF0, 0x30);
+    vm.write_port(0xF1, 0x1F);
+    vm.mark_vgm_loop();
+    for _ in 0..frames {
+        vm.run_frame();
+    }
+    vm.stop_vgm_log().unwrap()
+}
+
+#[test]
+fn playback() {
+    let file = record_tone(60);
+    let mut player = VgmPlayer::new(&file).unwrap();
+    assert_eq!("Tone", player.tags().track);
+    assert_eq!(3_579_545, player.clock());
+    assert!(player.has_ym2413());
+
+    let audio = player.render_to_end().unwrap();
+    assert!(player.is_finished());
+    assert_eq!(0xFE, player.psg().tone_period(0));
+    assert_eq!(0x1F, player.fm().unwrap().register(0x30));
+
+    let left: Vec<i16> = audio.iter().step_by(2).cloned().collect();
+    assert!(left.len() >= player.total_samples() as usize);
+    let mut high = true;
+    let mut edges = 0;
+    for sample in left[..player.total_samples() as usize].iter() {
+        if high && *sample < -4000 {
+            high = false;
+        } else if !high && *sample > 4000 {
+            high = true;
+            edges += 1;
+        }
+    }
+    assert!((436..=442).contains(&edges), "{} edges.", edges);
+}
+
+#[test]
+fn playback_fm_clock() {
+    let mut vm = Machine::new();
+    vm.set_fm_unit(true);
+    vm.start_vgm_log();
+    let sine = [0x01, 0x21, 0x3F, 0x00, 0xF0, 0xF0, 0x0F, 0x0F];
+    for (register, value) in sine.iter().enumerate() {
+        vm.write_port(0xF0, register as u8);
+        vm.write_port(0xF1, *value);
+    }
+    for (register, value) in [(0x30, 0x00), (0x10, 0x22), (0x20, 0x19)].iter() {
+        vm.write_port(0xF0, *register);
+        vm.write_port(0xF1, *value);
+    }
+    for _ in 0..60 {
+        vm.run_frame();
+    }
+    let mut file = vm.stop_vgm_log().unwrap();
+    // Halving the PSG's clock must not change the pitch of the YM2413, which has its own.
+    file[0x0C..0x10].copy_from_slice(&(3_579_545u32 / 2).to_le_bytes());
+
+    let mut player = VgmPlayer::new(&file).unwrap();
+    let audio = player.render_to_end().unwrap();
+    let mut high = true;
+    let mut edges = 0;
+    let length = player.total_samples() as usize;
+    for sample in audio.iter().step_by(2).take(length) {
+        if high && *sample < -1000 {
+            high = false;
+        } else if !high && *sample > 1000 {
+            high = true;
+            edges += 1;
+        }
+    }
+    assert!((435..=445).contains(&edges), "{} edges.", edges);
+}
+
+#[test]
+fn playback_loops() {
+    let file = record_tone(2);
+    let mut player = VgmPlayer::new(&file).unwrap();
+    player.set_loop_count(2);
+    let length = player.total_samples() + 2 * player.loop_samples();
+    player.render(length - 1).unwrap();
+    assert!(!player.is_finished());
+    player.render(2).unwrap();
+    assert!(player.is_finished());
+}
+
+#[test]
+fn playback_errors() {
+    assert_eq!(
+        Some(VgmError::Compressed),
+        VgmPlayer::new(&[0x1F, 0x8B, 0x08]).err()
+    );
+    assert_eq!(Some(VgmError::InvalidHeader), VgmPlayer::new(b"RIFF").err());
+
+    let mut file = record_tone(1);
+    file[0x2A] = 17;
+    assert_eq!(
+        Some(VgmError::UnsupportedNoise),
+        VgmPlayer::new(&file).err()
+    );
+
+    let mut file = record_tone(1);
+    let data = 0x34 + read_u32(&file, 0x34) as usize;
+    file[data] = 0x20;
+    let mut player = VgmPlayer::new(&file).unwrap();
+    assert_eq!(
+        Some(VgmError::UnknownCommand(0x20)),
+        player.render(100).err()
+    );
+}
+
+#[test]
+fn noise_shape() {
+    let mut file = record_tone(1);
+    assert_eq!(0x0009, VgmPlayer::new(&file).unwrap().psg().noise_taps());
+    assert_eq!(16, VgmPlayer::new(&file).unwrap().psg().noise_width());
+
+    file[0x28] = 0x03;
+    file[0x2A] = 15;
+    let player = VgmPlayer::new(&file).unwrap();
+    assert_eq!(0x0003, player.psg().noise_taps());
+    assert_eq!(15, player.psg().noise_width());
+
+    file[0x08] = 0x01;
+    file[0x09] = 0x01;
+    let player = VgmPlayer::new(&file).unwrap();
+    assert_eq!(0x0009, player.psg().noise_taps());
+    assert_eq!(16, player.psg().noise_width());
+}